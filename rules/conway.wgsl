@@ -0,0 +1,9 @@
+// Conway's Game of Life: B3/S23
+
+fn transition(state: u32, neighbors: u32) -> u32 {
+    if neighbors == 3u || (state > 0u && neighbors == 2u) {
+        return 1u;
+    }
+
+    return 0u;
+}
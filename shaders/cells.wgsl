@@ -0,0 +1,57 @@
+// Instanced quads of cells culled by `cull.wgsl`, the first instance is background of field
+
+// @view
+
+// @draw
+
+// Index of background instance
+let BACKGROUND: u32 = 0xffffffffu;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+struct CellInfo {
+    @location(1) idx: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) local: vec2<f32>,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: CellInfo,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Cells are centred at their coordinates
+    if instance.idx == BACKGROUND {
+        var size = vec2<f32>(f32(view.width), f32(view.height));
+        var local = model.position.xy * size + (size - 1.0) / 2.0;
+        out.clip_position = view.view_proj * vec4<f32>(local, 0.0, 1.0);
+        out.local = local;
+        out.color = palette.states[0];
+        return out;
+    }
+
+    var cell = vec2<f32>(f32(instance.idx % view.width), f32(instance.idx / view.width));
+    out.local = model.position.xy + cell;
+    out.clip_position = view.view_proj * vec4<f32>(out.local, 0.0, 1.0);
+
+    out.color = palette.states[min(life_field[instance.idx], 255u)];
+    var slot = rule_map[instance.idx];
+    if slot > 0u {
+        out.color = mix(out.color, region_tint(slot), 0.35);
+    }
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return grid(in.color, in.local);
+}
@@ -0,0 +1,34 @@
+// Culling of cells in view: indices of alive or tinted cells are appended to instances,
+// their count is instance count of indirect draw
+
+// @view
+
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+@group(2) @binding(0)
+var<storage, read_write> instances: array<u32>;
+
+@group(2) @binding(1)
+var<storage, read_write> args: DrawIndexedIndirect;
+
+@compute
+@workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= view.count_x || id.y >= view.count_y {
+        return;
+    }
+
+    var idx = (view.min_x + id.x) + (view.min_y + id.y) * view.width;
+    if life_field[idx] == 0u && rule_map[idx] == 0u {
+        return;
+    }
+
+    var instance = atomicAdd(&args.instance_count, 1u);
+    instances[instance] = idx;
+}
@@ -0,0 +1,55 @@
+// One level of density pyramid: each cell of level is the share of alive cells
+// among 2x2 cells of the level below, the first level is built from the field itself
+
+struct Level {
+    // Offset and size of level below in `density`
+    src_offset: u32,
+    src_width: u32,
+    src_height: u32,
+    dst_offset: u32,
+    dst_width: u32,
+    dst_height: u32,
+    // 1 if level below is the field
+    from_field: u32,
+};
+@group(0) @binding(0)
+var<uniform> level: Level;
+
+@group(0) @binding(1)
+var<storage, read_write> density: array<f32>;
+
+@group(1) @binding(0)
+var<storage, read> life_field: array<u32>;
+
+
+fn value(x: u32, y: u32) -> f32 {
+    if level.from_field == 1u {
+        return select(0.0, 1.0, life_field[x + y * level.src_width] > 0u);
+    }
+
+    return density[level.src_offset + x + y * level.src_width];
+}
+
+@compute
+@workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= level.dst_width || id.y >= level.dst_height {
+        return;
+    }
+
+    // Cells beyond odd sizes are not counted
+    var sum = 0.0;
+    var count = 0.0;
+    for (var dy = 0u; dy < 2u; dy = dy + 1u) {
+        for (var dx = 0u; dx < 2u; dx = dx + 1u) {
+            var x = id.x * 2u + dx;
+            var y = id.y * 2u + dy;
+            if x < level.src_width && y < level.src_height {
+                sum = sum + value(x, y);
+                count = count + 1.0;
+            }
+        }
+    }
+
+    density[level.dst_offset + id.x + id.y * level.dst_width] = sum / count;
+}
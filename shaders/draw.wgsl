@@ -0,0 +1,59 @@
+// Palette, grid lines and tints of rule regions shared by shaders drawing cells,
+// spliced at `// @draw` by `src/shader.rs` after `// @view`
+
+struct Palette {
+    background: vec4<f32>,
+    grid: vec4<f32>,
+    grid_major: vec4<f32>,
+    outline: vec4<f32>,
+    states: array<vec4<f32>, 256>,
+};
+@group(2) @binding(0)
+var<uniform> palette: Palette;
+
+// Blends grid lines over color of cell at `local` position of field.
+//
+// Lines are 1 pixel wide and fade out when cells get smaller than a few pixels,
+// major lines fade out when they get close to each other.
+fn grid(color: vec4<f32>, local: vec2<f32>) -> vec4<f32> {
+    if view.grid == 0u {
+        return color;
+    }
+
+    // Cells are centred at their coordinates, so edges are at halves
+    var inside = fract(local + 0.5);
+    var distance = min(inside, 1.0 - inside) * view.cell_size;
+    var coverage = clamp(1.0 - min(distance.x, distance.y), 0.0, 1.0);
+
+    // Index of edge nearest to each axis
+    var edge = vec2<i32>(floor(local + 0.5) + step(vec2<f32>(0.5), inside));
+    var major = 0.0;
+    if view.grid_major > 0u {
+        var n = i32(view.grid_major);
+        var on_x = distance.x <= distance.y && edge.x % n == 0;
+        var on_y = distance.y <= distance.x && edge.y % n == 0;
+        if on_x || on_y {
+            major = smoothstep(16.0, 32.0, view.cell_size * f32(n));
+        }
+    }
+
+    var minor = smoothstep(4.0, 8.0, view.cell_size);
+    var line_color = mix(palette.grid, palette.grid_major, major);
+    var fade = max(minor, major);
+
+    return mix(color, vec4<f32>(line_color.rgb, 1.0), coverage * fade * line_color.a);
+}
+
+// Tint of cells with region rule
+fn region_tint(slot: u32) -> vec4<f32> {
+    switch slot {
+        case 1u: { return vec4<f32>(0.0, 0.4, 1.0, 1.0); }
+        case 2u: { return vec4<f32>(0.0, 1.0, 0.3, 1.0); }
+        case 3u: { return vec4<f32>(1.0, 0.0, 1.0, 1.0); }
+        case 4u: { return vec4<f32>(0.0, 1.0, 1.0, 1.0); }
+        case 5u: { return vec4<f32>(1.0, 0.5, 0.0, 1.0); }
+        case 6u: { return vec4<f32>(0.5, 0.0, 1.0, 1.0); }
+        case 7u: { return vec4<f32>(1.0, 1.0, 0.0, 1.0); }
+        default: { return vec4<f32>(1.0, 1.0, 1.0, 1.0); }
+    }
+}
@@ -0,0 +1,101 @@
+// Fullscreen pass drawing cells of field under each pixel
+
+// @view
+
+// Levels of density pyramid from 1, see `src/density.rs`
+@group(0) @binding(1)
+var<storage, read> density: array<f32>;
+
+// @draw
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) clip: vec2<f32>,
+}
+
+// Share of alive cells in block of level containing cell, level 0 is the field
+fn density_at(level: u32, cell: vec2<u32>) -> f32 {
+    if level == 0u {
+        return select(0.0, 1.0, life_field[cell.x + cell.y * view.width] > 0u);
+    }
+
+    var width = view.width;
+    var height = view.height;
+    var offset = 0u;
+    for (var i = 1u; i < level; i = i + 1u) {
+        width = (width + 1u) / 2u;
+        height = (height + 1u) / 2u;
+        offset = offset + width * height;
+    }
+    width = (width + 1u) / 2u;
+
+    return density[offset + (cell.x >> level) + (cell.y >> level) * width];
+}
+
+// Color of zoomed out field, where a pixel covers many cells: brightness grows with density
+fn zoomed_out(cell: vec2<u32>) -> vec4<f32> {
+    var lod = min(log2(1.0 / view.cell_size), f32(view.levels));
+    var lower = u32(floor(lod));
+    var upper = min(lower + 1u, view.levels);
+    var share = mix(density_at(lower, cell), density_at(upper, cell), fract(lod));
+
+    return mix(palette.states[0], palette.states[1], sqrt(share));
+}
+
+// Coverage of 2 pixels wide outline around rectangle of field at `local` position
+fn outline(local: vec2<f32>) -> f32 {
+    if view.outline == 0u {
+        return 0.0;
+    }
+
+    // Distances in pixels to edges, negative outside of rectangle
+    var to_min = (local - view.outline_box.xy) * view.cell_size;
+    var to_max = (view.outline_box.zw - local) * view.cell_size;
+    var outside = -min(min(to_min, to_max), vec2<f32>(0.0));
+    if max(outside.x, outside.y) > 1.0 {
+        return 0.0;
+    }
+
+    var distance = min(abs(to_min), abs(to_max));
+    return clamp(1.0 - min(distance.x, distance.y), 0.0, 1.0);
+}
+
+// Single triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var clip = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(clip, 0.0, 1.0);
+    out.clip = clip;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Cells are centred at their coordinates
+    var local = (view.inverse * vec4<f32>(in.clip, 0.0, 1.0)).xy;
+    var cell = floor(local + 0.5);
+
+    if cell.x < 0.0 || cell.y < 0.0 || cell.x >= f32(view.width) || cell.y >= f32(view.height) {
+        discard;
+    }
+
+    var color: vec4<f32>;
+    if view.cell_size < 1.0 {
+        color = zoomed_out(vec2<u32>(cell));
+    } else {
+        var idx = u32(cell.x) + u32(cell.y) * view.width;
+        color = palette.states[min(life_field[idx], 255u)];
+
+        var slot = rule_map[idx];
+        if slot > 0u {
+            color = mix(color, region_tint(slot), 0.35);
+        }
+
+        color = grid(color, local);
+    }
+
+    return mix(color, palette.outline, outline(local));
+}
@@ -0,0 +1,30 @@
+// Translucent preview of pattern being placed
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+struct CellInfo {
+    @location(1) pos: vec2<f32>,
+    @location(2) idx: u32,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: CellInfo,
+) -> @builtin(position) vec4<f32> {
+    var shift = vec4<f32>(instance.pos, 0.0, 0.0);
+    return camera.view_proj * (vec4<f32>(model.position, 1.0) + shift);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.3, 0.8, 1.0, 0.5);
+}
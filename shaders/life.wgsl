@@ -1,18 +1,44 @@
+// Life kernel template. The rule's `evaluate` function is spliced in
+// at `@evaluate` marker, see `src/kernel.rs`.
+//
+// `evaluate(state, neighbourhood)` gets current state of cell and bitmask
+// of alive cells around it: `NW N NE W C E SW S SE` from the highest bit.
+// States of these cells are in `cells` in the same order.
+//
+// Cells with non-zero slot in `rule_map` use lookup table of region rule instead.
 
 struct FieldSize {
     width: u32,
     height: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+    // 0 - torus, 1 - dead cells beyond edges
+    boundary: u32,
 }
 
 @group(0) @binding(0)
 var<uniform> field_info: FieldSize;
 
+@group(0) @binding(2)
+var<storage, read_write> next_changed: array<atomic<u32>>;
+
+@group(0) @binding(3)
+var<storage, read_write> active_tiles: array<u32>;
+
 @group(1) @binding(0)
 var<storage, read> life_field: array<u32>;
 
 @group(2) @binding(0)
 var<storage, read_write> new_life_field: array<u32>;
 
+@group(3) @binding(1)
+var<storage, read> rule_map: array<u32>;
+
+@group(3) @binding(2)
+var<storage, read> region_rules: array<u32>;
+
+var<private> cells: array<u32, 9>;
+
 fn idx(x: u32, y: u32) -> u32 {
     var x_rem = x % field_info.width;
     var y_rem = y % field_info.height;
@@ -28,52 +54,71 @@ fn idx_y(idx: u32) -> u32 {
     return idx / field_info.width;
 }
 
+// @evaluate
+
+// One workgroup per active tile
 @compute
-@workgroup_size(32)
-fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
-    var x = global_invocation_id.x;
-    var y = global_invocation_id.y;
-    var current_idx = idx(x, y);
+@workgroup_size(8, 8)
+fn main(
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+) {
+    var tile = active_tiles[workgroup_id.x];
+    var x = (tile % field_info.tiles_x) * 8u + local_id.x;
+    var y = (tile / field_info.tiles_x) * 8u + local_id.y;
+    if x >= field_info.width || y >= field_info.height {
+        return;
+    }
 
-    // Neighbor count
-    var nc: u32 = 0u;
-    for (var i = 0u; i <= 2u; i++) {
-        for (var j = 0u; j <= 2u; j++) {
-            if (i == 1u && j == 1u) {
-                continue;
-            }
+    var current_idx = idx(x, y);
 
+    // Neighbourhood bitmask, north is +y
+    var neighbourhood: u32 = 0u;
+    for (var row = 0u; row <= 2u; row++) {
+        for (var i = 0u; i <= 2u; i++) {
+            var j = 2u - row;
             var x_neighbor = x + i;
             var y_neighbor = y + j;
+            var outside = x + i > field_info.width || y + j > field_info.height;
             if (x == 0u && i == 0u) {
                 x_neighbor = field_info.width - 1u;
+                outside = true;
             } else {
                 x_neighbor -= 1u;
             }
 
             if (y == 0u && j == 0u) {
                 y_neighbor = field_info.height - 1u;
+                outside = true;
             } else {
                 y_neighbor -= 1u;
             }
 
+            var neighbor_state = life_field[idx(x_neighbor, y_neighbor)];
+            if outside && field_info.boundary == 1u {
+                neighbor_state = 0u;
+            }
 
-            var neighbor_idx = idx(x_neighbor, y_neighbor);
-            if life_field[neighbor_idx] > 0u {
-                nc++;
+            neighbourhood = neighbourhood << 1u;
+            cells[row * 3u + i] = neighbor_state;
+            if neighbor_state > 0u {
+                neighbourhood |= 1u;
             }
         }
     }
 
     // Evaluate new state
-    var alive = life_field[current_idx] > 0u;
-    if alive && 2u <= nc && nc <= 3u {
-        // Will survive
-    } else if !alive && nc == 3u {
-        new_life_field[current_idx] = 1u;
-    } else if alive {
-        new_life_field[current_idx] = 0u;
+    var state = life_field[current_idx];
+    var new_state: u32;
+    var slot = rule_map[current_idx];
+    if slot == 0u {
+        new_state = evaluate(state, neighbourhood);
+    } else {
+        new_state = region_rules[(slot - 1u) * 512u + neighbourhood];
     }
+    new_life_field[current_idx] = new_state;
 
-
-}
\ No newline at end of file
+    if new_state != state {
+        atomicStore(&next_changed[tile], 1u);
+    }
+}
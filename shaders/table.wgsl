@@ -0,0 +1,8 @@
+// Lookup table rule, indexed by neighbourhood bitmask
+
+@group(3) @binding(0)
+var<storage, read> rule_table: array<u32>;
+
+fn evaluate(state: u32, neighbourhood: u32) -> u32 {
+    return rule_table[neighbourhood];
+}
@@ -0,0 +1,56 @@
+// Collects tiles which changed or have changed neighbours for the next step
+
+struct FieldSize {
+    width: u32,
+    height: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+    // 0 - torus, 1 - dead cells beyond edges
+    boundary: u32,
+}
+
+struct Dispatch {
+    x: atomic<u32>,
+    y: u32,
+    z: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> field_info: FieldSize;
+
+@group(0) @binding(1)
+var<storage, read> changed: array<u32>;
+
+@group(0) @binding(3)
+var<storage, read_write> active_tiles: array<u32>;
+
+@group(0) @binding(4)
+var<storage, read_write> dispatch: Dispatch;
+
+@compute
+@workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    var tile = global_invocation_id.x;
+    if tile >= field_info.tiles_x * field_info.tiles_y {
+        return;
+    }
+
+    var tile_x = tile % field_info.tiles_x;
+    var tile_y = tile / field_info.tiles_x;
+
+    var is_active = false;
+    for (var j = 0u; j <= 2u; j++) {
+        for (var i = 0u; i <= 2u; i++) {
+            var x = (tile_x + field_info.tiles_x + i - 1u) % field_info.tiles_x;
+            var y = (tile_y + field_info.tiles_y + j - 1u) % field_info.tiles_y;
+            if changed[x + y * field_info.tiles_x] > 0u {
+                is_active = true;
+            }
+        }
+    }
+
+    if is_active {
+        var slot = atomicAdd(&dispatch.x, 1u);
+        active_tiles[slot] = tile;
+    }
+}
@@ -0,0 +1,5 @@
+// Adapter for user supplied `transition(state, neighbors)` function
+
+fn evaluate(state: u32, neighbourhood: u32) -> u32 {
+    return transition(state, countOneBits(neighbourhood & ~0x10u));
+}
@@ -0,0 +1,22 @@
+// Golly rule tree, `rule_tree` is `[states, root, nodes...]`
+
+@group(3) @binding(0)
+var<storage, read> rule_tree: array<u32>;
+
+fn next_node(node: u32, cell: u32) -> u32 {
+    return rule_tree[node + min(cells[cell], rule_tree[0] - 1u)];
+}
+
+fn evaluate(state: u32, neighbourhood: u32) -> u32 {
+    // Tree visits cells in order: nw, ne, sw, se, n, w, e, s, c
+    var node = rule_tree[1];
+    node = next_node(node, 0u);
+    node = next_node(node, 2u);
+    node = next_node(node, 6u);
+    node = next_node(node, 8u);
+    node = next_node(node, 1u);
+    node = next_node(node, 3u);
+    node = next_node(node, 5u);
+    node = next_node(node, 7u);
+    return next_node(node, 4u);
+}
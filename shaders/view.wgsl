@@ -0,0 +1,36 @@
+// Field view, field and rule map shared by shaders of field, spliced at `// @view`
+// by `src/shader.rs`
+
+struct FieldView {
+    // Transform from field to clip space
+    view_proj: mat4x4<f32>,
+    // Transform from clip space to field
+    inverse: mat4x4<f32>,
+    width: u32,
+    height: u32,
+    // Cells in view, cut to field
+    min_x: u32,
+    min_y: u32,
+    count_x: u32,
+    count_y: u32,
+    // Size of cell in pixels
+    cell_size: f32,
+    // 1 if grid lines are drawn
+    grid: u32,
+    // Cells between major grid lines, 0 for none
+    grid_major: u32,
+    // Levels of density pyramid above the field
+    levels: u32,
+    // 1 if outline is drawn
+    outline: u32,
+    // Outlined rectangle of field: min corner in xy, max corner in zw
+    outline_box: vec4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> view: FieldView;
+
+@group(1) @binding(0)
+var<storage, read> life_field: array<u32>;
+
+@group(3) @binding(1)
+var<storage, read> rule_map: array<u32>;
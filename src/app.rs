@@ -1,25 +1,40 @@
 use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
+use anyhow::Context;
 use glam::Vec2;
-use log::info;
-use wgpu::{
-    Device, Instance, PrimitiveState, Queue, RenderPipeline, Surface, SurfaceConfiguration,
-};
+use log::{error, info};
+use wgpu::{Device, Instance, Queue, RenderPipeline, Surface, SurfaceConfiguration};
 use winit::{dpi::PhysicalPosition, event::VirtualKeyCode, window::Window};
 
 use crate::{
     bindable::{
-        BinableToRenderPass, BindableToVertexBuffers, Camera, CellPosInstances, FieldState,
-        HaveBindGroup,
+        BinableToRenderPass, BindableToVertexBuffers, Boundary, Camera, HaveBindGroup, Palette,
+        RULE_SLOTS, TILE_SIZE,
+    },
+    event_chain::{
+        DrawHandlerSubscriber, DropHandlerSubscriber, KeyboardHandlerSubscriber,
+        MouseHandlerSubscriber,
     },
-    event_chain::{DrawHandlerSubscriber, KeyboardHandlerSubscriber, MouseHandlerSubscriber},
     life::Life,
+    minimap::{Corner, Minimap},
     model::{Model, Quad},
+    pattern::{
+        self, apgcode, image::ImageOptions, macrocell::Macrocell, rle, Format, Loaded, Pattern,
+    },
+    recorder::{RecordFormat, Recorder},
+    renderer::{RenderMode, Renderer},
+    rule::{Rule, RuleFile, RuleTable},
+    screenshot::{Grid, Screenshot},
+    session::{Session, SessionCamera, SessionRule},
     shader::Shader,
-    text::FpsText,
+    stamp::Stamp,
+    text::{FpsText, MessageText},
+    theme::Theme,
 };
 
 pub struct App {
@@ -28,21 +43,49 @@ pub struct App {
     queue: Queue,
     config: SurfaceConfiguration,
 
-    render_pipeline: RenderPipeline,
+    renderer: Renderer,
+    ghost_pipeline: RenderPipeline,
+    render_mode: RenderMode,
 
     camera: Camera,
 
     quad: Quad,
 
-    instance_buffer: CellPosInstances,
-
     life: Life,
-    life_buffer: Arc<FieldState>,
+    palette: Palette,
+    /// Built-in themes and theme file, if any
+    themes: Vec<Theme>,
     paused: bool,
+    rule_file: Option<RuleFile>,
+    /// Slot of rule painted on rule map by clicks, if painting
+    paint_slot: Option<u32>,
+    export_format: Format,
+    /// Seed of initial random soup
+    seed: u64,
+    /// Cells between major grid lines shown by G
+    grid_major: u32,
+    recorder: Option<Recorder>,
+    /// Built-in patterns
+    library: Vec<Pattern>,
+    /// Pattern of library stamped by clicks, if stamping
+    stamp: Option<Stamp>,
+    minimap: Option<Minimap>,
+    cursor: PhysicalPosition<f64>,
+    /// Keys moving camera every frame while held, and modifiers
+    held: HashSet<VirtualKeyCode>,
+    /// Point of field pinned to cursor while dragging
+    drag: Option<Vec2>,
+    /// Camera positions and sizes of cell saved on number keys
+    bookmarks: [Option<(Vec2, f32)>; 10],
+    /// Centre of object followed by camera and generation it was found at
+    follow: Option<(Vec2, u64)>,
+    /// Loaded pattern larger than the field and its coordinates of top left cell of the field
+    sparse: Option<(Macrocell, i64, i64)>,
 
     fps: f32,
     previous_frame_time: Instant,
     fps_text: FpsText,
+    message_text: MessageText,
 }
 
 impl App {
@@ -104,75 +147,959 @@ impl App {
 
         let life_w = 1024;
         let life_h = 1024;
-        let life = Life::new(life_w, life_h, &device);
-        let life_buffer = life.life_buffer();
+        let seed = match std::env::var("LIFE_SEED") {
+            Ok(seed) => seed.parse().unwrap_or_else(|_| {
+                error!("Invalid seed `{}`, using random one", seed);
+                rand::random()
+            }),
+            Err(_) => rand::random(),
+        };
+        info!("Seed: {}", seed);
+
+        let mut life = Life::new(life_w, life_h, seed, &device);
+        let regions_env = std::env::var("LIFE_REGION_RULES").unwrap_or_else(|_| "B36/S23".into());
+        for rulestring in regions_env.split(',').filter(|r| !r.trim().is_empty()) {
+            let result =
+                RuleTable::parse(rulestring).and_then(|table| life.add_region_rule(table, &queue));
+            if let Err(e) = result {
+                error!("Failed to add region rule:\n{:#}", e);
+            }
+        }
 
-        let instance_buffer =
-            life.generate_cell_info((Vec2::ZERO, [life_w as f32, life_h as f32].into()), &device);
+        let rule_env = std::env::var("LIFE_RULE").ok();
+        let rule_file = rule_env
+            .as_ref()
+            .filter(|r| Path::new(r).is_file())
+            .map(RuleFile::new);
+        let export_env = std::env::var("LIFE_EXPORT_FORMAT").ok();
+        let export_format = export_env
+            .as_deref()
+            .map_or(Some(Format::Rle), Format::from_name)
+            .unwrap_or_else(|| {
+                error!("Unknown export format, using RLE");
+                Format::Rle
+            });
+        let grid_major = Self::grid_major().unwrap_or_else(|e| {
+            error!("{:#}, using 10", e);
+            10
+        });
+        let (themes, theme) = Self::themes();
+        let palette = Palette::new(themes[theme].clone(), &device);
 
-        // Shader init
-        let shader = Shader::new(&device, config.format);
         // Camera prepare
         let mut camera = Camera::new(size.width, size.height, &device);
         camera.set_position(Vec2::from_array([life_w as f32 / 2.0, life_h as f32 / 2.0]));
 
-        let render_pipeline_layout =
+        let renderer = Renderer::new(&life, &palette, config.format, &device);
+
+        let ghost_shader = Shader::ghost(&device, config.format);
+        let ghost_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[
-                    &camera.get_bind_layout(),
-                    &life.life_buffer().get_bind_layout(),
-                ],
+                label: Some("Ghost Pipeline Layout"),
+                bind_group_layouts: &[camera.get_bind_layout()],
                 push_constant_ranges: &[],
             });
-
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: shader.vertex_state(),
-            fragment: Some(shader.frag_state()),
-            primitive: PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: Default::default(),
-            multiview: None,
-        });
+        let ghost_pipeline = ghost_shader.create_pipeline(&ghost_pipeline_layout, &device);
 
         let quad = Quad::new(&device);
 
         let fps = 0.0;
         let previous_frame_time = Instant::now();
         let fps_text = FpsText::new(&device, config.format);
+        let message_text = MessageText::new(&device, config.format);
 
-        Arc::new(Mutex::new(Self {
+        let mut app = Self {
             surface,
             device,
             queue,
             config,
 
-            render_pipeline,
+            renderer,
+            ghost_pipeline,
+            render_mode: RenderMode::Fullscreen,
 
             camera,
 
             quad,
 
-            instance_buffer,
-
             life,
-            life_buffer,
+            palette,
+            themes,
             paused: false,
+            rule_file,
+            paint_slot: None,
+            export_format,
+            seed,
+            grid_major,
+            recorder: None,
+            library: Self::library(),
+            stamp: None,
+            minimap: None,
+            cursor: (0.0, 0.0).into(),
+            held: HashSet::new(),
+            drag: None,
+            bookmarks: [None; 10],
+            follow: None,
+            sparse: None,
 
             fps,
             previous_frame_time,
             fps_text,
-        }))
+            message_text,
+        };
+        match rule_env {
+            Some(rulestring) if app.rule_file.is_none() => {
+                let result = RuleTable::parse(&rulestring)
+                    .and_then(|table| app.set_rule(Rule::Table(table)));
+                if let Err(e) = result {
+                    error!("Failed to set rule:\n{:#}", e);
+                }
+            }
+            _ => app.reload_rule(),
+        }
+
+        if let Ok(path) = std::env::var("LIFE_PATTERN") {
+            if let Err(e) = app.load_pattern(Path::new(&path)) {
+                error!("Failed to load pattern from {}:\n{:#}", path, e);
+            }
+        }
+
+        Arc::new(Mutex::new(app))
+    }
+
+    /// Built-in themes followed by theme file, and index of theme chosen by `LIFE_THEME`.
+    ///
+    /// `LIFE_THEME` is a name of built-in theme or path to theme file.
+    fn themes() -> (Vec<Theme>, usize) {
+        let mut themes = Theme::builtin();
+
+        let name = match std::env::var("LIFE_THEME") {
+            Ok(name) => name,
+            Err(_) => return (themes, 0),
+        };
+
+        if let Some(index) = themes.iter().position(|t| t.name == name.trim()) {
+            return (themes, index);
+        }
+
+        match Theme::load(Path::new(&name)) {
+            Ok(theme) => themes.push(theme),
+            Err(e) => {
+                error!("Failed to load theme:\n{:#}", e);
+                return (themes, 0);
+            }
+        }
+
+        let index = themes.len() - 1;
+        (themes, index)
+    }
+
+    /// Switches to the next theme
+    fn cycle_theme(&mut self) {
+        let current = self
+            .themes
+            .iter()
+            .position(|t| t == self.palette.theme())
+            .unwrap_or(0);
+        let theme = self.themes[(current + 1) % self.themes.len()].clone();
+
+        info!("Theme: {}", theme.name);
+        self.message_text.info(format!("Theme: {}", theme.name));
+        self.palette.set_theme(theme, self.life.rule(), &self.queue);
+    }
+
+    fn set_rule(&mut self, rule: Rule) -> anyhow::Result<()> {
+        self.life.set_rule(rule, &self.queue, &self.device)?;
+        self.palette.set_rule(self.life.rule(), &self.queue);
+
+        Ok(())
+    }
+
+    /// Loads rule from rule file, if any, keeping current rule on error
+    fn reload_rule(&mut self) {
+        let rule_file = match self.rule_file.as_mut() {
+            Some(r) => r,
+            None => return,
+        };
+
+        let name = rule_file.path().display().to_string();
+        let result = rule_file
+            .load()
+            .and_then(|contents| Rule::from_file(rule_file.path(), &contents))
+            .and_then(|rule| self.set_rule(rule));
+
+        match result {
+            Ok(_) => info!("Loaded rule {}", self.life.rule().name()),
+            Err(e) => error!("Failed to load rule from {}:\n{:#}", name, e),
+        }
+    }
+
+    /// Reads pattern or image, switching to rule named by pattern
+    fn read_pattern(&mut self, path: &Path) -> anyhow::Result<Loaded> {
+        let (width, height) = (self.life.width(), self.life.height());
+        let is_image = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+
+        let pattern = if is_image {
+            let options = std::env::var("LIFE_IMAGE").unwrap_or_default();
+            let mut options = ImageOptions::parse(&options)?;
+            // Transition functions may use any state, images are usually meant as two-state
+            let states = self.life.rule().declared_states().unwrap_or(2) as usize;
+            options.colors = self
+                .palette
+                .colors(self.life.rule())
+                .iter()
+                .take(states)
+                .map(|&[r, g, b, _]| [r, g, b])
+                .collect();
+
+            Loaded::Pattern(pattern::image::load(path, &options, width, height)?)
+        } else {
+            pattern::load(path, width, height)?
+        };
+
+        let metadata = pattern.metadata();
+        info!(
+            "Loaded pattern {} by {}",
+            metadata.name.as_deref().unwrap_or("<unnamed>"),
+            metadata.author.as_deref().unwrap_or("<unknown>")
+        );
+        for comment in &metadata.comments {
+            info!("{}", comment);
+        }
+
+        if let Some(name) = &metadata.rule {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let result = Rule::from_name(name, dir).and_then(|rule| self.set_rule(rule));
+            if let Err(e) = result {
+                error!("Failed to set rule of pattern:\n{:#}", e);
+            }
+        }
+
+        Ok(pattern)
+    }
+
+    /// Replaces field with pattern, switching to rule named by pattern.
+    ///
+    /// Pattern is placed at `LIFE_PATTERN_POSITION` or at the centre of the field.
+    fn load_pattern(&mut self, path: &Path) -> anyhow::Result<()> {
+        let (width, height) = (self.life.width(), self.life.height());
+        let pattern = match self.read_pattern(path)? {
+            Loaded::Pattern(pattern) => pattern,
+            Loaded::Sparse(macrocell) => return self.load_sparse(macrocell),
+        };
+
+        let position = std::env::var("LIFE_PATTERN_POSITION").ok();
+        let (x, y) = match position.as_deref().and_then(|p| p.split_once(',')) {
+            Some((x, y)) => (
+                x.trim().parse().context("Invalid pattern position")?,
+                y.trim().parse().context("Invalid pattern position")?,
+            ),
+            None => (
+                width.saturating_sub(pattern.width()) / 2,
+                height.saturating_sub(pattern.height()) / 2,
+            ),
+        };
+
+        self.sparse = None;
+        self.life.clear(&self.queue);
+        self.life.place_pattern(x, y, &pattern, &self.queue)
+    }
+
+    /// Replaces field with centre of pattern larger than it, the pattern is kept
+    fn load_sparse(&mut self, macrocell: Macrocell) -> anyhow::Result<()> {
+        let (width, height) = (self.life.width(), self.life.height());
+        let (pattern_width, pattern_height) = macrocell.size();
+        info!(
+            "Pattern is {}x{}, field shows its centre, V moves it to the view",
+            pattern_width, pattern_height
+        );
+
+        let (window, left, top) = macrocell.centre(width, height);
+        self.sparse = Some((macrocell, left, top));
+
+        self.life.clear(&self.queue);
+        self.life.place_pattern(0, 0, &window, &self.queue)
+    }
+
+    /// Replaces field with part of loaded pattern larger than it centred at the view,
+    /// the view stays over the same cells of the pattern
+    fn recentre_sparse(&mut self) -> anyhow::Result<()> {
+        let (macrocell, left, top) = match &self.sparse {
+            Some(sparse) => sparse,
+            None => anyhow::bail!("No pattern larger than the field is loaded"),
+        };
+
+        // Rows of pattern go from the top, north is +y
+        let (width, height) = (self.life.width() as i64, self.life.height() as i64);
+        let centre = self.camera.position().round();
+        let x = left + (centre.x as i64).rem_euclid(width);
+        let y = top + height - 1 - (centre.y as i64).rem_euclid(height);
+
+        let (left, top) = (x - width / 2, y - height / 2);
+        let window = macrocell.region(left, top, width as u32, height as u32);
+        self.life.clear(&self.queue);
+        self.life.place_pattern(0, 0, &window, &self.queue)?;
+
+        let offset = self.camera.position() - centre;
+        let field_centre = Vec2::new((width / 2) as f32, (height - 1 - height / 2) as f32);
+        self.camera.set_position(field_centre + offset);
+        self.stop_following();
+        if let Some(sparse) = &mut self.sparse {
+            (sparse.1, sparse.2) = (left, top);
+        }
+
+        Ok(())
+    }
+
+    /// Places pattern over cells of field with its centre at cursor, or at the centre of view
+    fn drop_pattern(
+        &mut self,
+        path: &Path,
+        position: Option<PhysicalPosition<f64>>,
+    ) -> anyhow::Result<()> {
+        let pattern = match self.read_pattern(path)? {
+            Loaded::Pattern(pattern) => pattern,
+            Loaded::Sparse(macrocell) => return self.load_sparse(macrocell),
+        };
+
+        let centre = match position {
+            Some(position) => {
+                let clip = self.screen_space_to_clip(position);
+                self.camera.from_clip_space_to_local(clip)
+            }
+            None => self.camera.position(),
+        };
+
+        let (width, height) = (self.life.width() as i64, self.life.height() as i64);
+        let x = centre.x.round() as i64 - pattern.width() as i64 / 2;
+        let y = centre.y.round() as i64 - pattern.height() as i64 / 2;
+
+        self.life.place_pattern(
+            x.rem_euclid(width) as u32,
+            y.rem_euclid(height) as u32,
+            &pattern,
+            &self.queue,
+        )
+    }
+
+    /// Saves alive cells in view or on the whole field to pattern file in current directory
+    fn export_pattern(&self, whole_field: bool, format: Format) -> anyhow::Result<()> {
+        let (width, height) = (self.life.width(), self.life.height());
+        let (min, max) = match whole_field {
+            true => (Vec2::ZERO, Vec2::new(width as f32, height as f32)),
+            false => self.camera.view_box(),
+        };
+
+        let min_x = (min.x.floor().max(0.0) as u32).min(width);
+        let min_y = (min.y.floor().max(0.0) as u32).min(height);
+        let max_x = (max.x.ceil().max(0.0) as u32).min(width);
+        let max_y = (max.y.ceil().max(0.0) as u32).min(height);
+
+        let region = self.life.read_region(
+            min_x,
+            min_y,
+            max_x.saturating_sub(min_x),
+            max_y.saturating_sub(min_y),
+            &self.device,
+            &self.queue,
+        );
+
+        let mut pattern = region.cropped();
+        let rule = self.life.rule();
+        match rule.pattern_name() {
+            Some(name) => pattern.metadata_mut().rule = Some(name.to_owned()),
+            None => pattern
+                .metadata_mut()
+                .comments
+                .push(format!("Rule: {}", rule.name())),
+        }
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = PathBuf::from(format!("pattern-{}.{}", time, format.extension()));
+        pattern::save(&pattern, &path, format)?;
+
+        info!(
+            "Saved {}x{} pattern to {}",
+            pattern.width(),
+            pattern.height(),
+            path.display()
+        );
+
+        Ok(())
+    }
+
+    /// Path of session file from `LIFE_SESSION`
+    fn session_path() -> PathBuf {
+        std::env::var("LIFE_SESSION")
+            .unwrap_or_else(|_| "session.lifesession".into())
+            .into()
+    }
+
+    fn save_session(&self) -> anyhow::Result<()> {
+        let (width, height) = (self.life.width(), self.life.height());
+        let field = self
+            .life
+            .read_region(0, 0, width, height, &self.device, &self.queue);
+        let (format, source) = self.life.rule().source();
+        let rule_map = self.life.read_rule_map(&self.device, &self.queue);
+        let painted = (0..height).any(|y| rule_map.row(y).iter().any(|&slot| slot > 0));
+
+        let session = Session {
+            field: rle::write(&field),
+            rule: SessionRule {
+                format,
+                name: self.life.rule().name().to_owned(),
+                source: source.to_owned(),
+            },
+            region_rules: self
+                .life
+                .region_rules()
+                .iter()
+                .map(|rule| rule.name().to_owned())
+                .collect(),
+            rule_map: painted.then(|| rle::write(&rule_map)),
+            generation: self.life.generation(),
+            boundary: self.life.boundary(),
+            camera: SessionCamera {
+                position: self.camera.position().to_array(),
+                scale: self.camera.scale_factor(),
+            },
+            paused: self.paused,
+            seed: self.seed,
+        };
+
+        let path = Self::session_path();
+        session.save(&path)?;
+        info!("Saved session to {}", path.display());
+
+        Ok(())
+    }
+
+    /// Restores session, current state is kept on error
+    fn load_session(&mut self, path: &Path) -> anyhow::Result<()> {
+        let session = Session::load(path)?;
+
+        // Everything is checked before the first change
+        let (width, height) = (self.life.width(), self.life.height());
+        let field =
+            rle::parse(&session.field, width, height).context("Invalid field of session")?;
+        if (field.width(), field.height()) != (width, height) {
+            anyhow::bail!(
+                "Session field {}x{} doesn't match field {}x{}",
+                field.width(),
+                field.height(),
+                width,
+                height
+            );
+        }
+
+        let region_rules = session
+            .region_rules
+            .iter()
+            .map(|rule| RuleTable::parse(rule))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context("Invalid region rule of session")?;
+        if region_rules.len() >= RULE_SLOTS as usize {
+            anyhow::bail!("At most {} region rules are supported", RULE_SLOTS - 1);
+        }
+
+        let rule_map = match &session.rule_map {
+            Some(map) => rle::parse(map, width, height).context("Invalid rule map of session")?,
+            None => Pattern::new(width, height),
+        };
+        if (rule_map.width(), rule_map.height()) != (width, height) {
+            anyhow::bail!("Rule map of session doesn't match field");
+        }
+        let slots = (0..height).flat_map(|y| rule_map.row(y));
+        if let Some(slot) = slots.copied().find(|&s| s as usize > region_rules.len()) {
+            anyhow::bail!("Rule map of session uses empty slot {}", slot);
+        }
+
+        let rule = &session.rule;
+        let rule = Rule::from_source(rule.format, &rule.name, &rule.source)
+            .context("Invalid rule of session")?;
+        self.set_rule(rule)?;
+
+        self.sparse = None;
+        self.life.clear(&self.queue);
+        self.life.place_pattern(0, 0, &field, &self.queue)?;
+        self.life.set_region_rules(region_rules, &self.queue)?;
+        self.life.set_rule_map(&rule_map, &self.queue)?;
+        self.life.set_generation(session.generation);
+        self.life.set_boundary(session.boundary, &self.queue);
+        if self
+            .paint_slot
+            .is_some_and(|slot| slot as usize > self.life.region_rules().len())
+        {
+            self.paint_slot = Some(0);
+        }
+
+        self.camera
+            .set_position(Vec2::from_array(session.camera.position));
+        self.camera.set_scale_factor(session.camera.scale);
+        self.paused = session.paused;
+        self.seed = session.seed;
+
+        info!(
+            "Loaded session from {} at generation {}",
+            path.display(),
+            session.generation
+        );
+
+        Ok(())
+    }
+
+    /// Built-in patterns and objects of `LIFE_APGCODES`
+    fn library() -> Vec<Pattern> {
+        let mut library = pattern::library::patterns();
+
+        let codes = std::env::var("LIFE_APGCODES").unwrap_or_default();
+        for code in codes.split(',').filter(|c| !c.trim().is_empty()) {
+            match apgcode::decode(code) {
+                Ok(pattern) => library.push(pattern),
+                Err(e) => error!("Failed to decode apgcode:\n{:#}", e),
+            }
+        }
+
+        library
+    }
+
+    /// Apgcode of object nearest to cursor
+    fn object_apgcode(&self) -> anyhow::Result<String> {
+        /// Size of region around cursor object is searched in
+        const WINDOW: u32 = 64;
+
+        let rule = match self.life.rule() {
+            Rule::Table(table) => table,
+            _ => anyhow::bail!("Apgcodes are supported only for rulestring rules"),
+        };
+
+        let cursor = self.screen_space_to_clip(self.cursor);
+        let cell = self.camera.from_clip_space_to_local(cursor);
+        let (width, height) = (self.life.width() as i64, self.life.height() as i64);
+        let x = (cell.x.round() as i64 - WINDOW as i64 / 2).rem_euclid(width);
+        let y = (cell.y.round() as i64 - WINDOW as i64 / 2).rem_euclid(height);
+
+        let region = self.life.read_region(
+            x as u32,
+            y as u32,
+            WINDOW.min(width as u32),
+            WINDOW.min(height as u32),
+            &self.device,
+            &self.queue,
+        );
+
+        let object = apgcode::object_near(&region, WINDOW / 2, WINDOW / 2)
+            .context("No alive cells near cursor")?;
+
+        apgcode::encode(&object, rule)
+    }
+
+    /// Saves camera to bookmark, or flies to saved one
+    fn bookmark(&mut self, index: usize, save: bool) {
+        let message = match (save, self.bookmarks[index]) {
+            (true, _) => {
+                self.bookmarks[index] = Some((self.camera.position(), self.camera.scale_factor()));
+                format!("Saved bookmark {}", index)
+            }
+            (false, Some((position, scale))) => {
+                self.stop_following();
+                self.camera.fly_to(position, scale);
+                format!("Bookmark {}", index)
+            }
+            (false, None) => format!("No bookmark {}, Ctrl+{} saves it", index, index),
+        };
+
+        info!("{}", message);
+        self.message_text.info(message);
+    }
+
+    /// Zooms to fit all alive cells into window
+    fn zoom_to_fit(&mut self) -> anyhow::Result<()> {
+        /// Share of window left around cells
+        const PADDING: f32 = 0.1;
+
+        let (width, height) = (self.life.width(), self.life.height());
+        let field = self
+            .life
+            .read_region(0, 0, width, height, &self.device, &self.queue);
+        let (left, top, w, h) = field.bounding_box().context("No alive cells")?;
+
+        // Rows of pattern go from the top, cells are centred at their coordinates
+        let centre = Vec2::new(
+            left as f32 + (w - 1) as f32 / 2.0,
+            (height - top) as f32 - (h + 1) as f32 / 2.0,
+        );
+        let scale = (self.config.width as f32 / w as f32).min(self.config.height as f32 / h as f32);
+
+        self.stop_following();
+        self.camera.fly_to(centre, scale * (1.0 - PADDING));
+
+        Ok(())
+    }
+
+    /// Follows object under cursor, or stops following
+    fn toggle_follow(&mut self) -> anyhow::Result<()> {
+        if self.follow.is_some() {
+            self.stop_following();
+            return Ok(());
+        }
+
+        let cursor = self.screen_space_to_clip(self.cursor);
+        let cell = self.camera.from_clip_space_to_local(cursor);
+        let centre = self
+            .object_centre(cell)
+            .context("No alive cells near cursor")?;
+
+        self.follow = Some((centre, self.life.generation()));
+        self.camera.move_to(centre);
+        info!("Following object at {}, {}", centre.x, centre.y);
+
+        Ok(())
+    }
+
+    fn stop_following(&mut self) {
+        if self.follow.take().is_some() {
+            info!("Stopped following");
+        }
+    }
+
+    /// Moves camera after followed object once per generation
+    fn follow_object(&mut self) {
+        let (centre, generation) = match self.follow {
+            Some(follow) => follow,
+            None => return,
+        };
+        if generation == self.life.generation() {
+            return;
+        }
+
+        let centre = match self.object_centre(centre) {
+            Some(centre) => centre,
+            None => {
+                self.follow = None;
+                error!("Followed object is gone");
+                self.message_text.error("Followed object is gone".into());
+                return;
+            }
+        };
+
+        // Object crossing edge of torus is followed to the other side at once
+        let size = Vec2::new(self.life.width() as f32, self.life.height() as f32);
+        let wrap = |v: f32, size: f32| (v + 0.5).rem_euclid(size) - 0.5;
+        let wrapped = Vec2::new(wrap(centre.x, size.x), wrap(centre.y, size.y));
+        if wrapped != centre {
+            let position = self.camera.position() + wrapped - centre;
+            self.camera.set_position(position);
+        }
+
+        self.follow = Some((wrapped, self.life.generation()));
+        self.camera.move_to(wrapped);
+    }
+
+    /// Centre of bounding box of object nearest to cell, not wrapped around field
+    fn object_centre(&self, cell: Vec2) -> Option<Vec2> {
+        /// Size of region around cell object is searched in
+        const WINDOW: u32 = 64;
+
+        let (width, height) = (self.life.width(), self.life.height());
+        let (window_w, window_h) = (WINDOW.min(width), WINDOW.min(height));
+
+        // Bottom left corner of region
+        let x = cell.x.round() as i64 - window_w as i64 / 2;
+        let y = cell.y.round() as i64 - window_h as i64 / 2;
+        let region = self.life.read_region(
+            x.rem_euclid(width as i64) as u32,
+            y.rem_euclid(height as i64) as u32,
+            window_w,
+            window_h,
+            &self.device,
+            &self.queue,
+        );
+
+        // Rows of region go from the top
+        let (left, top, w, h) =
+            apgcode::object_box(&region, window_w / 2, window_h - 1 - window_h / 2)?;
+        let centre = Vec2::new(
+            left as f32 + (w - 1) as f32 / 2.0,
+            (window_h - top) as f32 - (h + 1) as f32 / 2.0,
+        );
+
+        Some(Vec2::new(x as f32, y as f32) + centre)
+    }
+
+    /// Selects next or previous pattern of library for stamping
+    fn cycle_stamp(&mut self, forward: bool) {
+        let count = self.library.len();
+        let index = match (&self.stamp, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(stamp), true) => (stamp.index() + 1) % count,
+            (Some(stamp), false) => (stamp.index() + count - 1) % count,
+        };
+
+        let pattern = self.library[index].clone();
+        info!(
+            "Stamping {}",
+            pattern.metadata().name.as_deref().unwrap_or("<unnamed>")
+        );
+        self.stamp = Some(Stamp::new(index, pattern));
+    }
+
+    /// Places stamp at cell under cursor
+    fn place_stamp(&mut self, cell: Vec2) {
+        let stamp = match &self.stamp {
+            Some(s) => s,
+            None => return,
+        };
+
+        let (width, height) = (self.life.width() as i64, self.life.height() as i64);
+        let (x, y) = stamp.origin_at(cell);
+        let result = self.life.place_pattern(
+            x.rem_euclid(width) as u32,
+            y.rem_euclid(height) as u32,
+            stamp.pattern(),
+            &self.queue,
+        );
+
+        if let Err(e) = result {
+            error!("Failed to stamp pattern:\n{:#}", e);
+        }
+    }
+
+    /// Cells between major grid lines from `LIFE_GRID_MAJOR`, 10 by default, 0 for no major lines
+    fn grid_major() -> anyhow::Result<u32> {
+        match std::env::var("LIFE_GRID_MAJOR") {
+            Ok(major) => major
+                .parse()
+                .with_context(|| format!("Invalid LIFE_GRID_MAJOR `{}`", major)),
+            Err(_) => Ok(10),
+        }
+    }
+
+    /// Shows or hides grid with major lines every `grid_major` cells
+    fn toggle_grid(&mut self) {
+        let grid = match self.renderer.field_view().grid() {
+            Some(_) => None,
+            None => Some(self.grid_major),
+        };
+
+        self.renderer.set_grid(grid, &self.queue);
+        info!("Grid: {}", if grid.is_some() { "on" } else { "off" });
+    }
+
+    /// Shows minimap of `LIFE_MINIMAP_SIZE` pixels in `LIFE_MINIMAP_CORNER` of window, or hides it
+    fn toggle_minimap(&mut self) -> anyhow::Result<()> {
+        if self.minimap.take().is_some() {
+            info!("Minimap: off");
+            return Ok(());
+        }
+
+        let size = match std::env::var("LIFE_MINIMAP_SIZE") {
+            Ok(size) => size
+                .parse()
+                .with_context(|| format!("Invalid minimap size `{}`", size))?,
+            Err(_) => 200,
+        };
+        let corner = std::env::var("LIFE_MINIMAP_CORNER").unwrap_or_else(|_| "top-right".into());
+        let corner = Corner::from_name(&corner)
+            .with_context(|| format!("Unknown minimap corner `{}`", corner))?;
+
+        let mut minimap = Minimap::new(
+            size,
+            corner,
+            (self.config.width, self.config.height),
+            &self.life,
+            self.renderer.density(),
+            &self.device,
+            &self.queue,
+        );
+        minimap.follow(&self.camera, &self.queue);
+        self.minimap = Some(minimap);
+        info!("Minimap: on");
+
+        Ok(())
+    }
+
+    fn toggle_boundary(&mut self) {
+        let boundary = match self.life.boundary() {
+            Boundary::Torus => Boundary::Dead,
+            Boundary::Dead => Boundary::Torus,
+        };
+
+        self.life.set_boundary(boundary, &self.queue);
+        info!("Boundary: {:?}", boundary);
+    }
+
+    /// Starts recording of cells in view to animation, or stops it and saves the animation.
+    ///
+    /// Recording is set by `LIFE_RECORD_*` environment variables.
+    fn toggle_recording(&mut self) -> anyhow::Result<()> {
+        if let Some(recorder) = self.recorder.take() {
+            let frames = recorder.frame_count();
+            let path = recorder.finish()?;
+            info!("Saved {} generations to {}", frames, path.display());
+
+            return Ok(());
+        }
+
+        let var = |name: &str, default: u32| -> anyhow::Result<u32> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .with_context(|| format!("Invalid {} `{}`", name, value)),
+                Err(_) => Ok(default),
+            }
+        };
+        let cell_size = var("LIFE_RECORD_CELL_SIZE", 4)?.clamp(1, 256);
+        let frames_per_generation = var("LIFE_RECORD_FRAMES_PER_GENERATION", 1)?;
+        let format = std::env::var("LIFE_RECORD_FORMAT").unwrap_or_else(|_| "gif".into());
+        let format = RecordFormat::from_name(&format)
+            .with_context(|| format!("Unknown recording format `{}`", format))?;
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = PathBuf::from(format!("recording-{}.{}", time, format.extension()));
+
+        // Region in view, cut to fit into texture
+        let (width, height) = (self.life.width(), self.life.height());
+        let max_cells = self.device.limits().max_texture_dimension_2d / cell_size;
+        let (min, max) = self.camera.view_box();
+        let min_x = (min.x.floor().max(0.0) as u32).min(width - 1);
+        let min_y = (min.y.floor().max(0.0) as u32).min(height - 1);
+        let max_x = (max.x.ceil().max(0.0) as u32).min(width);
+        let max_y = (max.y.ceil().max(0.0) as u32).min(height);
+        let region_w = max_x.saturating_sub(min_x).clamp(1, max_cells);
+        let region_h = max_y.saturating_sub(min_y).clamp(1, max_cells);
+
+        let mut recorder = Recorder::new(
+            &path,
+            (min_x, min_y, region_w, region_h),
+            cell_size,
+            format,
+            frames_per_generation,
+            &self.life,
+            &self.palette,
+            &self.device,
+            &self.queue,
+        )?;
+        recorder.capture(&self.life, &self.palette, &self.device, &self.queue)?;
+        self.recorder = Some(recorder);
+
+        info!(
+            "Recording {}x{} cells at {},{}",
+            region_w, region_h, min_x, min_y
+        );
+
+        Ok(())
+    }
+
+    /// Saves image of field in view to PNG file.
+    ///
+    /// Image is of window size or `LIFE_SCREENSHOT_SIZE`, e.g. `3840x2160`, showing the same
+    /// width of field. With `LIFE_SCREENSHOT_RENDERER=cpu` cells in view are drawn on CPU instead,
+    /// without tints of region rules and background around the field.
+    fn take_screenshot(&self) -> anyhow::Result<()> {
+        let (width, height) = match std::env::var("LIFE_SCREENSHOT_SIZE") {
+            Ok(size) => size
+                .split_once('x')
+                .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+                .filter(|&(w, h)| w > 0 && h > 0)
+                .with_context(|| format!("Invalid screenshot size `{}`", size))?,
+            Err(_) => (self.config.width, self.config.height),
+        };
+
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        if width > max_size || height > max_size {
+            anyhow::bail!("Screenshot can't be larger than {}x{}", max_size, max_size);
+        }
+
+        let scale = self.camera.scale_factor() * width as f32 / self.config.width as f32;
+        let cpu = std::env::var("LIFE_SCREENSHOT_RENDERER").is_ok_and(|r| r == "cpu");
+
+        let screenshot = if cpu {
+            let (x, y, w, h) = self.renderer.field_view().view_cells();
+            if w == 0 || h == 0 {
+                anyhow::bail!("No cells in view");
+            }
+
+            let region = self.life.read_region(x, y, w, h, &self.device, &self.queue);
+            let colors = self.palette.colors(self.life.rule());
+            let theme = self.palette.theme();
+            let grid = self.renderer.field_view().grid().map(|major| Grid {
+                major,
+                origin: (x, y),
+                colors: [theme.grid, theme.grid_major],
+            });
+            Screenshot::rasterize(
+                &region,
+                scale.round().max(1.0) as u32,
+                &colors,
+                grid.as_ref(),
+            )
+        } else {
+            let mut camera = Camera::new(width, height, &self.device);
+            camera.set_position(self.camera.position());
+            camera.set_scale_factor(scale);
+
+            Screenshot::render(
+                &camera,
+                (width, height),
+                self.render_mode,
+                self.renderer.field_view().grid(),
+                &self.life,
+                &self.palette,
+                &self.device,
+                &self.queue,
+            )
+        };
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = PathBuf::from(format!("screenshot-{}.png", time));
+        screenshot.save(&path)?;
+
+        let (width, height) = screenshot.size();
+        info!(
+            "Saved {}x{} screenshot to {}",
+            width,
+            height,
+            path.display()
+        );
+
+        Ok(())
+    }
+
+    /// Records current generation, stopping recording after `LIFE_RECORD_GENERATIONS`
+    fn record(&mut self) {
+        let recorder = match self.recorder.as_mut() {
+            Some(r) => r,
+            None => return,
+        };
+
+        if let Err(e) = recorder.capture(&self.life, &self.palette, &self.device, &self.queue) {
+            error!("Failed to record, recording is stopped:\n{:#}", e);
+            self.recorder = None;
+            return;
+        }
+
+        let limit = std::env::var("LIFE_RECORD_GENERATIONS")
+            .ok()
+            .and_then(|l| l.parse::<usize>().ok());
+        if limit.is_some_and(|l| recorder.frame_count() > l) {
+            if let Err(e) = self.toggle_recording() {
+                error!("Failed to save recording:\n{:#}", e);
+            }
+        }
     }
 
     pub fn update(&mut self) {
@@ -181,17 +1108,95 @@ impl App {
         self.fps = 1.0 / (frame_time.as_secs_f32());
         self.previous_frame_time = now;
 
+        if self.rule_file.as_mut().is_some_and(RuleFile::changed) {
+            self.reload_rule();
+        }
+
         if !self.paused {
             self.life.step(&self.queue, &self.device);
         }
+        self.record();
 
+        self.follow_object();
+        self.move_camera(frame_time.as_secs_f32());
         if self.camera.update(&self.queue) {
-            // camera updated rebuild view box
-            let view_box = self.camera.view_box();
-            self.instance_buffer = self.life.generate_cell_info(view_box, &self.device);
+            self.renderer.set_camera(&self.camera, &self.queue);
+            if let Some(minimap) = self.minimap.as_mut() {
+                minimap.follow(&self.camera, &self.queue);
+            }
         }
     }
 
+    /// Pans and zooms camera with held keys, zoom is anchored at cursor
+    fn move_camera(&mut self, dt: f32) {
+        /// Zoom per second while key is held
+        const ZOOM_SPEED: f32 = 4.0;
+        /// Longer frames are cut to avoid jumps after stalls
+        const MAX_DT: f32 = 0.1;
+
+        let dt = dt.min(MAX_DT);
+        let held = |key| self.held.contains(&key) as i32 as f32;
+
+        let direction = Vec2::new(
+            held(VirtualKeyCode::D) - held(VirtualKeyCode::A),
+            held(VirtualKeyCode::W) - held(VirtualKeyCode::S),
+        );
+        self.camera.steer(direction);
+
+        let zoom = held(VirtualKeyCode::Up) - held(VirtualKeyCode::Down);
+        if zoom != 0.0 {
+            let cursor = self.screen_space_to_clip(self.cursor);
+            self.camera.zoom_at(ZOOM_SPEED.powf(zoom * dt), cursor);
+        }
+
+        self.camera.animate(dt);
+    }
+
+    /// Selects next or previous rule slot to paint
+    fn cycle_paint_slot(&mut self, forward: bool) {
+        let slots = self.life.region_rules().len() as u32 + 1;
+        if let Some(slot) = self.paint_slot {
+            let slot = if forward {
+                (slot + 1) % slots
+            } else {
+                (slot + slots - 1) % slots
+            };
+
+            self.paint_slot = Some(slot);
+            info!("Painting rule {}", self.slot_name(slot));
+        }
+    }
+
+    fn toggle_rule_painting(&mut self) {
+        self.paint_slot = match self.paint_slot {
+            Some(_) => None,
+            None => Some(self.life.region_rules().len().min(1) as u32),
+        };
+
+        match self.paint_slot {
+            Some(slot) => info!("Rule painting on, painting {}", self.slot_name(slot)),
+            None => info!("Rule painting off"),
+        }
+    }
+
+    fn slot_name(&self, slot: u32) -> &str {
+        match slot {
+            0 => self.life.rule().name(),
+            _ => self.life.region_rules()[slot as usize - 1].name(),
+        }
+    }
+
+    /// Paints square of tile size centered at cell with rule slot
+    fn paint_rule(&mut self, x: i64, y: i64, slot: u32) {
+        let half = TILE_SIZE as i64 / 2;
+        let (width, height) = (self.life.width() as i64, self.life.height() as i64);
+        let left = (x - half).rem_euclid(width) as u32;
+        let bottom = (y - half).rem_euclid(height) as u32;
+
+        self.life
+            .set_rule_region(left, bottom, TILE_SIZE, TILE_SIZE, slot, &self.queue);
+    }
+
     fn screen_space_to_clip(&self, position: PhysicalPosition<f64>) -> Vec2 {
         let w = (position.x / (self.config.width as f64)) as f32;
         let h = (1.0 - position.y / (self.config.height as f64)) as f32;
@@ -206,12 +1211,24 @@ impl DrawHandlerSubscriber for App {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.reconfigure();
+
+            let size = (self.config.width, self.config.height);
+            if let Some(minimap) = self.minimap.as_mut() {
+                minimap.place(size, &self.queue);
+            }
         }
     }
 
     fn draw(&mut self) -> Result<(), wgpu::SurfaceError> {
         self.update();
 
+        // Cell under cursor changes with camera too
+        let cursor = self.screen_space_to_clip(self.cursor);
+        let cell = self.camera.from_clip_space_to_local(cursor);
+        if let Some(stamp) = self.stamp.as_mut() {
+            stamp.move_to(cell, &self.device);
+        }
+
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -223,46 +1240,51 @@ impl DrawHandlerSubscriber for App {
                 label: Some("Render Encoder"),
             });
 
-        {
+        self.renderer.render(
+            self.render_mode,
+            &self.life,
+            &self.palette,
+            self.minimap.as_ref(),
+            &view,
+            &mut encoder,
+            &self.queue,
+        );
+
+        if let Some(ghost) = self.stamp.as_ref().and_then(Stamp::ghost) {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
+                label: Some("Ghost Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.3,
-                            g: 0.3,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Load,
                         store: true,
                     },
                 })],
                 depth_stencil_attachment: None,
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_pipeline(&self.ghost_pipeline);
             self.camera.bind_to_render_pass(&mut render_pass, 0, &[]);
-            self.life_buffer
-                .bind_to_render_pass(&mut render_pass, 1, &[]);
-
-            self.instance_buffer
-                .bind_vertex_to_render_pass(&mut render_pass, 1);
-
-            self.quad
-                .draw(&mut render_pass, 0..self.instance_buffer.len() as _);
+            ghost.bind_vertex_to_render_pass(&mut render_pass, 1);
+            self.quad.draw(&mut render_pass, 0..ghost.len());
         }
 
         // draw fps
         self.fps_text
             .draw(self.fps, &self.device, &mut encoder, &view);
 
+        let size = (self.config.width, self.config.height);
+        self.message_text
+            .draw(&self.device, &mut encoder, &view, size);
+
         // submit will accept anything that implements IntoIter
         self.fps_text.submit();
+        self.message_text.submit();
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
         self.fps_text.recall();
+        self.message_text.recall();
 
         Ok(())
     }
@@ -277,28 +1299,235 @@ impl KeyboardHandlerSubscriber for App {
     fn key_pressed(&mut self, key_code: &winit::event::VirtualKeyCode) {
         info!("Pressed: {:?}", key_code);
         match key_code {
-            VirtualKeyCode::W => self.camera.up(),
-            VirtualKeyCode::S => self.camera.down(),
-            VirtualKeyCode::A => self.camera.left(),
-            VirtualKeyCode::D => self.camera.right(),
-            VirtualKeyCode::Up => self.camera.zoom_in(),
-            VirtualKeyCode::Down => self.camera.zoom_out(),
+            VirtualKeyCode::W
+            | VirtualKeyCode::A
+            | VirtualKeyCode::S
+            | VirtualKeyCode::D
+            | VirtualKeyCode::Up
+            | VirtualKeyCode::Down => {
+                if matches!(
+                    key_code,
+                    VirtualKeyCode::W | VirtualKeyCode::A | VirtualKeyCode::S | VirtualKeyCode::D
+                ) {
+                    self.stop_following();
+                }
+                self.held.insert(*key_code);
+            }
+            VirtualKeyCode::LControl | VirtualKeyCode::RControl => {
+                self.held.insert(*key_code);
+            }
+            VirtualKeyCode::Key0
+            | VirtualKeyCode::Key1
+            | VirtualKeyCode::Key2
+            | VirtualKeyCode::Key3
+            | VirtualKeyCode::Key4
+            | VirtualKeyCode::Key5
+            | VirtualKeyCode::Key6
+            | VirtualKeyCode::Key7
+            | VirtualKeyCode::Key8
+            | VirtualKeyCode::Key9 => {
+                let index = match key_code {
+                    VirtualKeyCode::Key1 => 1,
+                    VirtualKeyCode::Key2 => 2,
+                    VirtualKeyCode::Key3 => 3,
+                    VirtualKeyCode::Key4 => 4,
+                    VirtualKeyCode::Key5 => 5,
+                    VirtualKeyCode::Key6 => 6,
+                    VirtualKeyCode::Key7 => 7,
+                    VirtualKeyCode::Key8 => 8,
+                    VirtualKeyCode::Key9 => 9,
+                    _ => 0,
+                };
+                let save = self.held.contains(&VirtualKeyCode::LControl)
+                    || self.held.contains(&VirtualKeyCode::RControl);
+                self.bookmark(index, save);
+            }
+            VirtualKeyCode::Z => {
+                if let Err(e) = self.zoom_to_fit() {
+                    error!("Failed to zoom to fit:\n{:#}", e);
+                    self.message_text.error(format!("{:#}", e));
+                }
+            }
+            VirtualKeyCode::V => {
+                if let Err(e) = self.recentre_sparse() {
+                    error!("Failed to move pattern:\n{:#}", e);
+                    self.message_text.error(format!("{:#}", e));
+                }
+            }
+            VirtualKeyCode::L => {
+                if let Err(e) = self.toggle_follow() {
+                    error!("Failed to follow:\n{:#}", e);
+                    self.message_text.error(format!("{:#}", e));
+                }
+            }
             VirtualKeyCode::Space => self.paused = !self.paused,
             VirtualKeyCode::Period if self.paused => self.life.step(&self.queue, &self.device),
+            VirtualKeyCode::R => self.reload_rule(),
+            VirtualKeyCode::P => self.toggle_rule_painting(),
+            VirtualKeyCode::F2 | VirtualKeyCode::F3 | VirtualKeyCode::F4 => {
+                let (whole_field, format) = match key_code {
+                    VirtualKeyCode::F2 => (false, self.export_format),
+                    VirtualKeyCode::F3 => (true, self.export_format),
+                    _ => (true, Format::Macrocell),
+                };
+
+                if let Err(e) = self.export_pattern(whole_field, format) {
+                    error!("Failed to export pattern:\n{:#}", e);
+                }
+            }
+            VirtualKeyCode::F5 => {
+                if let Err(e) = self.save_session() {
+                    error!("Failed to save session:\n{:#}", e);
+                }
+            }
+            VirtualKeyCode::F9 => {
+                if let Err(e) = self.load_session(&Self::session_path()) {
+                    error!("Failed to load session:\n{:#}", e);
+                }
+            }
+            VirtualKeyCode::B => self.toggle_boundary(),
+            VirtualKeyCode::T => self.cycle_theme(),
+            VirtualKeyCode::G => self.toggle_grid(),
+            VirtualKeyCode::M => {
+                if let Err(e) = self.toggle_minimap() {
+                    error!("Failed to show minimap:\n{:#}", e);
+                }
+            }
+            VirtualKeyCode::I => {
+                self.render_mode = match self.render_mode {
+                    RenderMode::Fullscreen => RenderMode::Instanced,
+                    RenderMode::Instanced => RenderMode::Fullscreen,
+                };
+                info!("Render mode: {:?}", self.render_mode);
+            }
+            VirtualKeyCode::C => match self.object_apgcode() {
+                Ok(code) => {
+                    info!("Apgcode: {}", code);
+                    self.message_text.info(code);
+                }
+                Err(e) => {
+                    error!("Failed to compute apgcode:\n{:#}", e);
+                    self.message_text.error(format!("{:#}", e));
+                }
+            },
+            VirtualKeyCode::Tab => self.cycle_stamp(true),
+            VirtualKeyCode::Grave => self.cycle_stamp(false),
+            VirtualKeyCode::Escape => self.stamp = None,
+            VirtualKeyCode::Q | VirtualKeyCode::E => {
+                if let Some(stamp) = self.stamp.as_mut() {
+                    stamp.rotate(*key_code == VirtualKeyCode::E);
+                }
+            }
+            VirtualKeyCode::F => {
+                if let Some(stamp) = self.stamp.as_mut() {
+                    stamp.flip();
+                }
+            }
+            VirtualKeyCode::F12 => {
+                if let Err(e) = self.take_screenshot() {
+                    error!("Failed to take screenshot:\n{:#}", e);
+                }
+            }
+            VirtualKeyCode::F7 => {
+                if let Err(e) = self.toggle_recording() {
+                    error!("Failed to record:\n{:#}", e);
+                }
+            }
+            VirtualKeyCode::LBracket => self.cycle_paint_slot(false),
+            VirtualKeyCode::RBracket => self.cycle_paint_slot(true),
             _ => {}
         }
     }
+
+    fn key_released(&mut self, key_code: &winit::event::VirtualKeyCode) {
+        self.held.remove(key_code);
+    }
+
+    fn focus_lost(&mut self) {
+        self.held.clear();
+    }
 }
 
 impl MouseHandlerSubscriber for App {
     fn clicked(&mut self, position: PhysicalPosition<f64>) {
+        // Minimap is over the field
+        let jump = self
+            .minimap
+            .as_ref()
+            .and_then(|m| m.field_position(position));
+        if let Some(target) = jump {
+            self.stop_following();
+            self.camera.move_to(target);
+            return;
+        }
+
         let converted = self.screen_space_to_clip(position);
         let local_click = self.camera.from_clip_space_to_local(converted);
 
+        if self.stamp.is_some() {
+            self.place_stamp(local_click);
+            return;
+        }
+
+        if let Some(slot) = self.paint_slot {
+            let x = local_click.x.round() as i64;
+            let y = local_click.y.round() as i64;
+            self.paint_rule(x, y, slot);
+            return;
+        }
+
         self.life.set_cell(
             local_click.x.round() as u32,
             local_click.y.round() as u32,
             &self.queue,
         );
     }
+
+    fn moved(&mut self, position: PhysicalPosition<f64>) {
+        self.cursor = position;
+    }
+
+    fn scrolled(&mut self, position: PhysicalPosition<f64>, lines: f32) {
+        /// Zoom per line of wheel
+        const WHEEL_ZOOM: f32 = 1.2;
+
+        let cursor = self.screen_space_to_clip(position);
+        self.camera.zoom_at(WHEEL_ZOOM.powf(lines), cursor);
+    }
+
+    fn drag_started(&mut self, position: PhysicalPosition<f64>) {
+        self.stop_following();
+        let cursor = self.screen_space_to_clip(position);
+        self.drag = Some(self.camera.from_clip_space_to_local(cursor));
+    }
+
+    fn dragged(&mut self, position: PhysicalPosition<f64>) {
+        if let Some(pinned) = self.drag {
+            let cursor = self.screen_space_to_clip(position);
+            let under_cursor = self.camera.from_clip_space_to_local(cursor);
+            self.camera
+                .set_position(self.camera.position() + pinned - under_cursor);
+        }
+    }
+
+    fn drag_ended(&mut self, _: PhysicalPosition<f64>) {
+        self.drag = None;
+    }
+}
+
+impl DropHandlerSubscriber for App {
+    fn file_dropped(&mut self, path: &Path, position: Option<PhysicalPosition<f64>>) {
+        info!("Dropped: {}", path.display());
+
+        let result = match Session::sniff(path) {
+            true => self.load_session(path),
+            false => self.drop_pattern(path, position),
+        };
+
+        if let Err(e) = result {
+            error!("Failed to load {}:\n{:#}", path.display(), e);
+            self.message_text
+                .error(format!("Failed to load {}: {:#}", path.display(), e));
+        }
+    }
 }
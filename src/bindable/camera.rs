@@ -13,14 +13,39 @@ pub const OPENGL_TO_WGPU_MATRIX: Mat4 = Mat4::from_cols_array(
     ]
 );
 
+/// Rate of easing of zoom, glides and pan speed per second, higher is snappier
+const EASING: f32 = 12.0;
+
+/// Speed of pan in pixels per second, so cells pass faster when zoomed out
+const PAN_SPEED: f32 = 800.0;
+
+/// Pan and zoom slower than this are stopped
+const REST: f32 = 1e-3;
+
+/// Range of sizes of cell in pixels zoom is kept in, so scale doesn't underflow or overflow
+const MIN_SCALE: f32 = 1.0 / 1024.0;
+const MAX_SCALE: f32 = 256.0;
+
 pub struct Camera {
     position: Vec2,
     scale: f32,
     update_required: bool,
 
+    /// Half of screen size in pixels
+    half_size: Vec2,
+    /// Scale zoom eases to
+    target_scale: f32,
+    /// Offset from centre of screen in pixels of point staying in place while zooming
+    anchor: Vec2,
+    /// Position camera glides to
+    target_position: Option<Vec2>,
+    /// Pan speed in pixels per second, kept after keys are released for inertia
+    velocity: Vec2,
+    /// Direction of pan this frame
+    steer: Vec2,
+
     view: Mat4,
     ortho: Mat4,
-    speed: f32,
 
     buffer: Buffer,
     bind_group: BindGroup,
@@ -70,9 +95,15 @@ impl Camera {
             scale: 1.0,
             update_required: false,
 
+            half_size: Vec2::ONE,
+            target_scale: 1.0,
+            anchor: Vec2::ZERO,
+            target_position: None,
+            velocity: Vec2::ZERO,
+            steer: Vec2::ZERO,
+
             view: Mat4::IDENTITY,
             ortho: Mat4::IDENTITY,
-            speed: 5.0,
 
             buffer,
             bind_group_layout,
@@ -90,6 +121,7 @@ impl Camera {
 
         let w = width as f32 / 2.0;
         let h = height as f32 / 2.0;
+        self.half_size = Vec2::new(w, h);
         self.ortho = Mat4::orthographic_rh(-w, w, -h, h, 0.1, 100.0);
     }
 
@@ -105,52 +137,101 @@ impl Camera {
         false
     }
 
-    fn build_raw(ortho: &Mat4, view: &Mat4) -> Mat4 {
-        OPENGL_TO_WGPU_MATRIX.mul_mat4(ortho).mul_mat4(view)
+    /// Transform from field to clip space
+    pub fn view_proj(&self) -> Mat4 {
+        Self::build_raw(&self.ortho, &self.view)
     }
 
-    pub fn up(&mut self) {
-        self.translate(Vec2::NEG_Y * self.speed)
+    fn build_raw(ortho: &Mat4, view: &Mat4) -> Mat4 {
+        OPENGL_TO_WGPU_MATRIX.mul_mat4(ortho).mul_mat4(view)
     }
 
-    pub fn down(&mut self) {
-        self.translate(Vec2::Y * self.speed)
+    /// Pans camera this frame in `direction`, e.g. `Vec2::Y` for up, zero to slow down
+    pub fn steer(&mut self, direction: Vec2) {
+        self.steer = direction.normalize_or_zero();
     }
 
-    pub fn left(&mut self) {
-        self.translate(Vec2::X * self.speed)
+    /// Zooms by `factor` keeping field under `clip` position of screen in place
+    pub fn zoom_at(&mut self, factor: f32, clip: Vec2) {
+        self.target_scale = (self.target_scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.anchor = clip * self.half_size;
     }
 
-    pub fn right(&mut self) {
-        self.translate(Vec2::NEG_X * self.speed)
+    /// Glides to position, see [`Camera::set_position`] for a jump
+    pub fn move_to(&mut self, position: Vec2) {
+        self.target_position = Some(position);
     }
 
-    pub fn zoom_in(&mut self) {
-        self.scale(1.1);
+    /// Glides to position zooming to size of cell at the centre of screen
+    pub fn fly_to(&mut self, position: Vec2, scale: f32) {
+        self.target_position = Some(position);
+        self.target_scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+        self.anchor = Vec2::ZERO;
     }
 
-    pub fn zoom_out(&mut self) {
-        self.scale(0.9);
-    }
+    /// Advances zoom, glide and pan by `dt` seconds, eased toward their targets
+    pub fn animate(&mut self, dt: f32) {
+        let t = 1.0 - (-EASING * dt).exp();
+        let (position, scale) = (self.position, self.scale);
+
+        // Zoom in log scale, so each step changes size by the same ratio
+        let ratio = self.target_scale / self.scale;
+        if ratio != 1.0 {
+            let point = self.position + self.anchor / self.scale;
+            self.scale = if (ratio - 1.0).abs() > REST {
+                self.scale * ratio.powf(t)
+            } else {
+                self.target_scale
+            };
+            self.position = point - self.anchor / self.scale;
+        }
 
-    pub fn scale(&mut self, factor: f32) {
-        self.update_required = true;
+        self.velocity += (self.steer * PAN_SPEED - self.velocity) * t;
+        if self.velocity.length() < REST * PAN_SPEED {
+            self.velocity = Vec2::ZERO;
+        }
+        self.position += self.velocity / self.scale * dt;
+
+        if let Some(target) = self.target_position {
+            self.position += (target - self.position) * t;
+            if (target - self.position).length() * self.scale < REST {
+                self.position = target;
+                self.target_position = None;
+            }
+        }
 
-        self.scale *= factor;
-        self.view = Mat4::from_scale(Vec3::splat(factor)).mul_mat4(&self.view);
+        if self.position != position || self.scale != scale {
+            self.rebuild_view();
+        }
     }
 
-    pub fn translate(&mut self, translate: Vec2) {
-        self.update_required = true;
-
-        self.position = self.position.mul_add(Vec2::ONE, translate);
-        self.view = self
-            .view
-            .mul_mat4(&Mat4::from_translation(translate.extend(0.0)))
+    /// Point of field at the centre of screen
+    pub fn position(&self) -> Vec2 {
+        self.position
     }
 
+    /// Jumps to position, stopping glide and pan
     pub fn set_position(&mut self, position: Vec2) {
         self.position = position;
+        self.target_position = None;
+        self.velocity = Vec2::ZERO;
+        self.rebuild_view();
+    }
+
+    /// Size of cell in pixels
+    pub fn scale_factor(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets size of cell at once, stopping zoom. Scale is kept in the range of zoom,
+    /// NaN is taken as the smallest scale.
+    pub fn set_scale_factor(&mut self, scale: f32) {
+        let scale = match scale.is_nan() {
+            true => MIN_SCALE,
+            false => scale.clamp(MIN_SCALE, MAX_SCALE),
+        };
+        self.scale = scale;
+        self.target_scale = scale;
         self.rebuild_view();
     }
 
@@ -165,6 +246,7 @@ impl Camera {
             Mat4::from_scale(Vec3::splat(self.scale)).mul_mat4(&Mat4::look_at_rh(eye, center, up));
     }
 
+    #[allow(clippy::wrong_self_convention)]
     pub fn from_clip_space_to_local(&self, clip_coord: Vec2) -> Vec2 {
         let clip4 = clip_coord.extend(0.0).extend(1.0);
         let raw = Self::build_raw(&self.ortho, &self.view);
@@ -0,0 +1,154 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, BufferUsages, Device, Queue};
+
+use super::{BindableToComputePass, BindableToVertexBuffers, HaveBindGroup, HaveBuffer};
+
+/// Instance standing for the whole field, drawn with color of dead cells below other cells
+pub const BACKGROUND: u32 = u32::MAX;
+
+/// Arguments of `draw_indexed_indirect`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+/// Indices of visible cells, written by culling compute pass, and arguments to draw them.
+///
+/// First instance is always the background of field.
+pub struct CellInstances {
+    index_count: u32,
+
+    buffer: Buffer,
+    args: Buffer,
+    bind_group: BindGroup,
+    bind_group_layout: BindGroupLayout,
+}
+
+impl CellInstances {
+    const ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![1 => Uint32];
+
+    /// Creates instances of up to `cell_count` cells, each drawn with `index_count` indices
+    pub fn new(cell_count: u32, index_count: u32, device: &Device) -> Self {
+        use wgpu::util::BufferInitDescriptor;
+        use wgpu::{
+            BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
+            ShaderStages,
+        };
+
+        let mut indices = vec![0; cell_count as usize + 1];
+        indices[0] = BACKGROUND;
+
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Cell instances buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: BufferUsages::STORAGE | BufferUsages::VERTEX,
+        });
+
+        let args = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Cell instances indirect buffer"),
+            contents: bytemuck::cast_slice(&[Self::initial_args(index_count)]),
+            usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_DST,
+        });
+
+        let entry = |binding| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Cell instances bind group layout"),
+            entries: &[entry(0), entry(1)],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Cell instances bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: args.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            index_count,
+
+            buffer,
+            args,
+            bind_group,
+            bind_group_layout,
+        }
+    }
+
+    fn initial_args(index_count: u32) -> DrawIndexedIndirect {
+        DrawIndexedIndirect {
+            index_count,
+            instance_count: 1,
+            first_index: 0,
+            base_vertex: 0,
+            first_instance: 0,
+        }
+    }
+
+    /// Leaves only the background, should be called before culling
+    pub fn reset(&self, queue: &Queue) {
+        let args = Self::initial_args(self.index_count);
+        queue.write_buffer(&self.args, 0, bytemuck::cast_slice(&[args]));
+    }
+
+    /// Buffer of `DrawIndexedIndirect` arguments
+    pub fn indirect_buffer(&self) -> &Buffer {
+        &self.args
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+impl HaveBindGroup for CellInstances {
+    fn get_bind_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    fn get_bind(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
+
+impl BindableToComputePass for CellInstances {}
+
+impl HaveBuffer for CellInstances {
+    fn get_buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+impl BindableToVertexBuffers for CellInstances {}
+
+impl Drop for CellInstances {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+        self.args.destroy();
+    }
+}
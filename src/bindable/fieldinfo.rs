@@ -1,31 +1,104 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device};
+use wgpu::{
+    util::DeviceExt, BindGroup, BindGroupLayout, Buffer, BufferUsages, CommandEncoder, Device,
+    Queue,
+};
+
+use serde::{Deserialize, Serialize};
 
 use super::{BindableToComputePass, HaveBindGroup};
 
+/// Width and height of tile in cells, matches workgroup size of kernel
+pub const TILE_SIZE: u32 = 8;
+
+/// What is beyond edges of field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    /// Opposite edges are connected
+    Torus = 0,
+    /// Cells beyond edges are always dead
+    Dead = 1,
+}
+
 /// Hold size information about field
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 struct FieldInfoRaw {
     width: u32,
     height: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+    boundary: u32,
+    _padding: [u32; 3],
 }
 
+/// Size of field and activity of its tiles.
+///
+/// Field is divided into tiles, kernel marks tiles whose cells changed.
+/// Only tiles which changed or have changed neighbours are computed in the next step,
+/// their list and arguments of indirect dispatch are built on GPU.
 pub struct FieldInfo {
     field_info: FieldInfoRaw,
 
     buffer: Buffer,
+    changed: Buffer,
+    next_changed: Buffer,
+    active_tiles: Buffer,
+    active_count: Buffer,
+    dispatch: Buffer,
+
     bind_group: BindGroup,
     bind_group_layout: BindGroupLayout,
 }
 
 impl FieldInfo {
     pub fn new(width: u32, height: u32, device: &Device) -> Self {
-        let field_info = FieldInfoRaw { width, height };
+        let tiles_x = width.div_ceil(TILE_SIZE);
+        let tiles_y = height.div_ceil(TILE_SIZE);
+        let field_info = FieldInfoRaw {
+            width,
+            height,
+            tiles_x,
+            tiles_y,
+            boundary: Boundary::Torus as u32,
+            _padding: [0; 3],
+        };
+
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Field info buffer"),
             contents: bytemuck::cast_slice(&[field_info]),
-            usage: wgpu::BufferUsages::UNIFORM,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        // Everything is changed at start
+        let tiles = vec![1u32; (tiles_x * tiles_y) as usize];
+        let tile_buffer = |label, usage| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&tiles),
+                usage: BufferUsages::STORAGE | usage,
+            })
+        };
+
+        let changed = tile_buffer("Changed tiles buffer", BufferUsages::COPY_DST);
+        let next_changed = tile_buffer(
+            "Next changed tiles buffer",
+            BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+        );
+        let active_tiles = tile_buffer("Active tiles buffer", BufferUsages::empty());
+
+        // Counted as `[x, y, z]` of dispatch, can't be indirect buffer as it is bound to kernel
+        let active_count = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Active tiles count buffer"),
+            contents: bytemuck::cast_slice(&[0u32, 1, 1]),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+        });
+
+        let dispatch = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Active tiles dispatch buffer"),
+            contents: bytemuck::cast_slice(&[0u32, 1, 1]),
+            usage: BufferUsages::INDIRECT | BufferUsages::COPY_DST,
         });
 
         use wgpu::{
@@ -33,33 +106,68 @@ impl FieldInfo {
             ShaderStages,
         };
 
+        let entry = |binding, ty| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let read = wgpu::BufferBindingType::Storage { read_only: true };
+        let read_write = wgpu::BufferBindingType::Storage { read_only: false };
+
         let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             label: Some("Life's field bind group layout"),
-            entries: &[BindGroupLayoutEntry {
-                binding: 0,
-                visibility: ShaderStages::COMPUTE,
-                ty: BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
+            entries: &[
+                entry(0, wgpu::BufferBindingType::Uniform),
+                entry(1, read),
+                entry(2, read_write),
+                entry(3, read_write),
+                entry(4, read_write),
+            ],
         });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Life's field bind group"),
             layout: &bind_group_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: changed.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: next_changed.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: active_tiles.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: active_count.as_entire_binding(),
+                },
+            ],
         });
 
         Self {
             field_info,
 
             buffer,
+            changed,
+            next_changed,
+            active_tiles,
+            active_count,
+            dispatch,
+
             bind_group,
             bind_group_layout,
         }
@@ -72,6 +180,88 @@ impl FieldInfo {
     pub fn height(&self) -> u32 {
         self.field_info.height
     }
+
+    pub fn boundary(&self) -> Boundary {
+        match self.field_info.boundary {
+            0 => Boundary::Torus,
+            _ => Boundary::Dead,
+        }
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary, queue: &Queue) {
+        self.field_info.boundary = boundary as u32;
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.field_info]));
+    }
+
+    pub fn tile_count(&self) -> u32 {
+        self.field_info.tiles_x * self.field_info.tiles_y
+    }
+
+    /// Buffer with arguments for indirect dispatch of active tiles
+    pub fn dispatch_buffer(&self) -> &Buffer {
+        &self.dispatch
+    }
+
+    /// Resets active tiles count before they are collected
+    pub fn reset_active(&self, queue: &Queue) {
+        queue.write_buffer(&self.active_count, 0, bytemuck::cast_slice(&[0u32, 1, 1]));
+    }
+
+    /// Fills dispatch buffer with collected active tiles count
+    pub fn prepare_dispatch(&self, encoder: &mut CommandEncoder) {
+        encoder.copy_buffer_to_buffer(
+            &self.active_count,
+            0,
+            &self.dispatch,
+            0,
+            self.dispatch.size(),
+        );
+    }
+
+    /// Makes tiles changed in this step visible to the next one
+    pub fn swap_changed(&self, encoder: &mut CommandEncoder) {
+        encoder.copy_buffer_to_buffer(&self.next_changed, 0, &self.changed, 0, self.changed.size());
+        encoder.clear_buffer(&self.next_changed, 0, None);
+    }
+
+    /// Marks tile containing cell as changed, so it is computed in the next step
+    pub fn mark_changed(&self, x: u32, y: u32, queue: &Queue) {
+        let tile = x / TILE_SIZE + y / TILE_SIZE * self.field_info.tiles_x;
+        let offset = tile as usize * std::mem::size_of::<u32>();
+        queue.write_buffer(&self.changed, offset as u64, bytemuck::cast_slice(&[1u32]));
+    }
+
+    /// Marks tiles intersecting rectangle of cells as changed, rectangle wraps around field
+    pub fn mark_region_changed(&self, x: u32, y: u32, width: u32, height: u32, queue: &Queue) {
+        let FieldInfoRaw {
+            tiles_x, tiles_y, ..
+        } = self.field_info;
+
+        let first_x = x / TILE_SIZE;
+        let first_y = y / TILE_SIZE;
+        let count_x = ((x + width).div_ceil(TILE_SIZE) - first_x).min(tiles_x);
+        let count_y = ((y + height).div_ceil(TILE_SIZE) - first_y).min(tiles_y);
+
+        let tiles = vec![1u32; count_x as usize];
+        for j in 0..count_y {
+            let row = (first_y + j) % tiles_y * tiles_x;
+
+            // Part of row which wraps around is written at its start
+            let first = ((tiles_x - first_x) as usize).min(tiles.len());
+            for (start, run) in [(first_x, &tiles[..first]), (0, &tiles[first..])] {
+                let offset = (row + start) as usize * std::mem::size_of::<u32>();
+                if !run.is_empty() {
+                    queue.write_buffer(&self.changed, offset as u64, bytemuck::cast_slice(run));
+                }
+            }
+        }
+    }
+
+    /// Marks all tiles as changed, e.g. when rule changes
+    pub fn mark_all_changed(&self, queue: &Queue) {
+        let tiles = vec![1u32; self.tile_count() as usize];
+        queue.write_buffer(&self.changed, 0, bytemuck::cast_slice(&tiles));
+    }
 }
 
 impl HaveBindGroup for FieldInfo {
@@ -88,6 +278,11 @@ impl BindableToComputePass for FieldInfo {}
 
 impl Drop for FieldInfo {
     fn drop(&mut self) {
-        self.buffer.destroy()
+        self.buffer.destroy();
+        self.changed.destroy();
+        self.next_changed.destroy();
+        self.active_tiles.destroy();
+        self.active_count.destroy();
+        self.dispatch.destroy();
     }
 }
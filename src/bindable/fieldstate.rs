@@ -1,5 +1,8 @@
+use std::ops::Range;
+
 use wgpu::{
     util::DeviceExt, BindGroup, BindGroupLayout, Buffer, BufferUsages, CommandEncoder, Device,
+    Queue,
 };
 
 use super::{BinableToRenderPass, BindableToComputePass, HaveBindGroup, HaveBuffer};
@@ -11,7 +14,7 @@ pub struct FieldState {
 }
 
 impl FieldState {
-    pub fn new(state: &Vec<u32>, device: &Device, read_only: bool) -> Self {
+    pub fn new(state: &[u32], device: &Device, read_only: bool) -> Self {
         use wgpu::util::BufferInitDescriptor;
         use wgpu::{
             BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
@@ -20,12 +23,12 @@ impl FieldState {
 
         let buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: Some("life buffer"),
-            contents: bytemuck::cast_slice(&state),
+            contents: bytemuck::cast_slice(state),
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
         });
 
         let visibility = if read_only {
-            ShaderStages::COMPUTE | ShaderStages::VERTEX
+            ShaderStages::COMPUTE | ShaderStages::VERTEX | ShaderStages::FRAGMENT
         } else {
             ShaderStages::COMPUTE
         };
@@ -65,6 +68,13 @@ impl FieldState {
 
         encoder.copy_buffer_to_buffer(&other.buffer, 0, &self.buffer, 0, self.buffer.size())
     }
+
+    /// Reads states of cells of index ranges one after another, blocks until they are available.
+    ///
+    /// Only the ranges are copied from GPU.
+    pub fn read_ranges(&self, ranges: &[Range<usize>], device: &Device, queue: &Queue) -> Vec<u32> {
+        super::read_ranges(&self.buffer, ranges, device, queue)
+    }
 }
 
 impl HaveBindGroup for FieldState {
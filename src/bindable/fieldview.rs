@@ -0,0 +1,200 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec2};
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue};
+
+use super::{BinableToRenderPass, BindableToComputePass, Camera, HaveBindGroup, HaveBuffer};
+use crate::density::DensityPyramid;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct FieldViewRaw {
+    /// Transform from field to clip space
+    view_proj: Mat4,
+    /// Transform from clip space to field
+    inverse: Mat4,
+    width: u32,
+    height: u32,
+    /// Cells in view, cut to field
+    min_x: u32,
+    min_y: u32,
+    count_x: u32,
+    count_y: u32,
+    /// Size of cell in pixels
+    cell_size: f32,
+    /// Whether grid lines are drawn
+    grid: u32,
+    /// Cells between major grid lines, 0 for none
+    grid_major: u32,
+    /// Levels of density pyramid above the field
+    levels: u32,
+    /// Whether outline is drawn
+    outline: u32,
+    _padding: u32,
+    /// Outlined rectangle of field as min and max corners
+    outline_box: [f32; 4],
+}
+
+/// What part of field is drawn: camera transform, its inverse, size of field, cells in view, grid
+/// and outlined rectangle.
+///
+/// Density pyramid is bound with it for drawing zoomed out field.
+pub struct FieldView {
+    raw: FieldViewRaw,
+
+    buffer: Buffer,
+    bind_group: BindGroup,
+    bind_group_layout: BindGroupLayout,
+}
+
+impl FieldView {
+    pub fn new(width: u32, height: u32, density: &DensityPyramid, device: &Device) -> Self {
+        use wgpu::{
+            BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
+            ShaderStages,
+        };
+
+        let raw = FieldViewRaw {
+            view_proj: Mat4::IDENTITY,
+            inverse: Mat4::IDENTITY,
+            width,
+            height,
+            min_x: 0,
+            min_y: 0,
+            count_x: width,
+            count_y: height,
+            cell_size: 1.0,
+            grid: 0,
+            grid_major: 0,
+            levels: density.levels(),
+            outline: 0,
+            _padding: 0,
+            outline_box: [0.0; 4],
+        };
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Field view buffer"),
+            contents: bytemuck::cast_slice(&[raw]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Field view bind group layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX
+                        | ShaderStages::FRAGMENT
+                        | ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Field view bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: density.get_buffer().as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            raw,
+
+            buffer,
+            bind_group,
+            bind_group_layout,
+        }
+    }
+
+    /// Follows camera, should be called when camera is updated
+    pub fn set_camera(&mut self, camera: &Camera, queue: &Queue) {
+        self.raw.view_proj = camera.view_proj();
+        self.raw.inverse = self.raw.view_proj.inverse();
+        self.raw.cell_size = camera.scale_factor();
+
+        // Cells are centred at their coordinates
+        let (min, max) = camera.view_box();
+        let raw = &mut self.raw;
+        let cell = |v: f32, size: u32| ((v + 0.5).floor().max(0.0) as u32).min(size);
+        raw.min_x = cell(min.x, raw.width);
+        raw.min_y = cell(min.y, raw.height);
+        raw.count_x = cell(max.x + 1.0, raw.width) - raw.min_x;
+        raw.count_y = cell(max.y + 1.0, raw.height) - raw.min_y;
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.raw]));
+    }
+
+    /// Size of cell in pixels
+    pub fn cell_size(&self) -> f32 {
+        self.raw.cell_size
+    }
+
+    /// Cells between major grid lines, if grid is shown
+    pub fn grid(&self) -> Option<u32> {
+        (self.raw.grid != 0).then_some(self.raw.grid_major)
+    }
+
+    /// Shows grid with major lines every `major` cells, 0 for no major lines, or hides it
+    pub fn set_grid(&mut self, grid: Option<u32>, queue: &Queue) {
+        self.raw.grid = grid.is_some() as u32;
+        self.raw.grid_major = grid.unwrap_or(0);
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.raw]));
+    }
+
+    /// Outlines rectangle of field between `min` and `max` corners, or hides outline
+    pub fn set_outline(&mut self, outline: Option<(Vec2, Vec2)>, queue: &Queue) {
+        self.raw.outline = outline.is_some() as u32;
+        if let Some((min, max)) = outline {
+            self.raw.outline_box = [min.x, min.y, max.x, max.y];
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.raw]));
+    }
+
+    /// Cells in view as `min_x`, `min_y`, `count_x`, `count_y`
+    pub fn view_cells(&self) -> (u32, u32, u32, u32) {
+        let raw = &self.raw;
+        (raw.min_x, raw.min_y, raw.count_x, raw.count_y)
+    }
+}
+
+impl HaveBindGroup for FieldView {
+    fn get_bind_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    fn get_bind(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
+
+impl BindableToComputePass for FieldView {}
+impl BinableToRenderPass for FieldView {}
+
+impl Drop for FieldView {
+    fn drop(&mut self) {
+        self.buffer.destroy()
+    }
+}
@@ -1,19 +1,37 @@
-use wgpu::Buffer;
+use std::ops::Range;
+
 use wgpu::{BindGroup, BindGroupLayout, ComputePass, DynamicOffset, RenderPass};
+use wgpu::{Buffer, BufferUsages, Device, Queue};
+
+mod cellinstances;
+pub use cellinstances::CellInstances;
 
 mod cellpos;
 pub use cellpos::CellPos;
 pub use cellpos::CellPosInstances;
 
 mod fieldinfo;
+pub use fieldinfo::Boundary;
 pub use fieldinfo::FieldInfo;
+pub use fieldinfo::TILE_SIZE;
 
 mod fieldstate;
 pub use fieldstate::FieldState;
 
+mod fieldview;
+pub use fieldview::FieldView;
+
 mod camera;
 pub use camera::Camera;
 
+mod ruledata;
+pub use ruledata::RuleData;
+pub use ruledata::RULE_SLOTS;
+
+mod palette;
+pub use palette::Color;
+pub use palette::Palette;
+
 pub trait HaveBindGroup {
     /// Returns layout for binding
     fn get_bind_layout(&self) -> &BindGroupLayout;
@@ -57,3 +75,53 @@ pub trait BindableToVertexBuffers: HaveBuffer {
         rp.set_vertex_buffer(slot, self.get_buffer().slice(..))
     }
 }
+
+/// Reads `u32`s of buffer back from GPU, blocks until they are available.
+///
+/// Buffer must have `COPY_SRC` usage.
+fn read_buffer(buffer: &Buffer, device: &Device, queue: &Queue) -> Vec<u32> {
+    let words = buffer.size() as usize / std::mem::size_of::<u32>();
+    read_ranges(buffer, std::slice::from_ref(&(0..words)), device, queue)
+}
+
+/// Reads ranges of indices of `u32`s of buffer one after another, blocks until they are
+/// available. Only the ranges are copied to staging buffer.
+///
+/// Buffer must have `COPY_SRC` usage.
+fn read_ranges(
+    buffer: &Buffer,
+    ranges: &[Range<usize>],
+    device: &Device,
+    queue: &Queue,
+) -> Vec<u32> {
+    let word = std::mem::size_of::<u32>() as u64;
+    let size = ranges.iter().map(|r| r.len() as u64).sum::<u64>() * word;
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Staging buffer"),
+        size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&Default::default());
+    let mut offset = 0;
+    for range in ranges.iter().filter(|r| !r.is_empty()) {
+        let len = range.len() as u64 * word;
+        encoder.copy_buffer_to_buffer(buffer, range.start as u64 * word, &staging, offset, len);
+        offset += len;
+    }
+    queue.submit(Some(encoder.finish()));
+
+    let slice = staging.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::Maintain::Wait);
+
+    let data = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+    staging.destroy();
+
+    data
+}
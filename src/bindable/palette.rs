@@ -0,0 +1,146 @@
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, BufferUsages, Device, Queue};
+
+use super::{BinableToRenderPass, HaveBindGroup};
+use crate::{
+    rule::{Rule, MAX_STATES},
+    theme::Theme,
+};
+
+pub type Color = [f32; 4];
+
+/// Colors of theme and cell states
+pub struct Palette {
+    theme: Theme,
+
+    buffer: Buffer,
+    bind_group: BindGroup,
+    bind_group_layout: BindGroupLayout,
+}
+
+impl Palette {
+    pub fn new(theme: Theme, device: &Device) -> Self {
+        use wgpu::util::BufferInitDescriptor;
+        use wgpu::{
+            BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
+            ShaderStages,
+        };
+
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Palette buffer"),
+            contents: bytemuck::cast_slice(&Self::uniform(
+                &theme,
+                &Self::default_colors(&theme, 2),
+            )),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Palette bind group layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Palette bind group"),
+            layout: &bind_group_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            theme,
+
+            buffer,
+            bind_group,
+            bind_group_layout,
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Switches theme, keeping colors defined by rule
+    pub fn set_theme(&mut self, theme: Theme, rule: &Rule, queue: &Queue) {
+        self.theme = theme;
+        self.set_rule(rule, queue);
+    }
+
+    /// Resets colors to theme's for rule's states and applies colors defined by rule
+    pub fn set_rule(&self, rule: &Rule, queue: &Queue) {
+        let colors = Self::uniform(&self.theme, &self.colors(rule));
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&colors));
+    }
+
+    /// Colors of all states for rule
+    pub fn colors(&self, rule: &Rule) -> Vec<Color> {
+        let mut colors = Self::default_colors(&self.theme, rule.states());
+        for &(state, rgb) in rule.colors() {
+            let [r, g, b] = rgb.map(|c| c as f32 / 255.0);
+            colors[state as usize] = [r, g, b, 1.0];
+        }
+
+        colors
+    }
+
+    /// Contents of uniform: background, grid, major grid lines and outline followed by colors
+    /// of states
+    fn uniform(theme: &Theme, states: &[Color]) -> Vec<Color> {
+        let mut colors = vec![
+            theme.background,
+            theme.grid,
+            theme.grid_major,
+            theme.outline,
+        ];
+        colors.extend_from_slice(states);
+
+        colors
+    }
+
+    /// Alive states go from theme's alive color to its last state color
+    fn default_colors(theme: &Theme, states: u32) -> Vec<Color> {
+        let mut colors = vec![theme.last_state; MAX_STATES as usize];
+        colors[0] = theme.dead;
+
+        for state in 1..states {
+            let t = if states > 2 {
+                (state - 1) as f32 / (states - 2) as f32
+            } else {
+                0.0
+            };
+
+            colors[state as usize] =
+                [0, 1, 2, 3].map(|i| theme.alive[i] + (theme.last_state[i] - theme.alive[i]) * t);
+        }
+
+        colors
+    }
+}
+
+impl HaveBindGroup for Palette {
+    fn get_bind_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    fn get_bind(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
+
+impl BinableToRenderPass for Palette {}
+
+impl Drop for Palette {
+    fn drop(&mut self) {
+        self.buffer.destroy()
+    }
+}
@@ -0,0 +1,171 @@
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, BufferUsages, Device, Queue};
+
+use super::{BinableToRenderPass, BindableToComputePass, HaveBindGroup};
+
+/// Number of rule slots in the rule map, slot 0 is the main rule
+pub const RULE_SLOTS: u32 = 8;
+
+/// Size of lookup table of region rule
+const TABLE_SIZE: u64 = 512;
+
+/// Rules for compute kernel.
+///
+/// Holds the main rule's data, e.g. lookup table, and the rule map: rule slot of every cell.
+/// Cells of slots other than 0 use lookup tables of region rules instead of the main rule.
+pub struct RuleData {
+    buffer: Buffer,
+    map: Buffer,
+    regions: Buffer,
+
+    bind_group: BindGroup,
+    bind_group_layout: BindGroupLayout,
+}
+
+impl RuleData {
+    pub fn new(data: &[u32], cell_count: usize, device: &Device) -> Self {
+        use wgpu::util::BufferInitDescriptor;
+        use wgpu::{BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, ShaderStages};
+
+        let buffer = Self::create_data_buffer(data, device);
+
+        let map = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Rule map buffer"),
+            contents: bytemuck::cast_slice(&vec![0u32; cell_count]),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+        });
+
+        let regions = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Region rules buffer"),
+            size: (RULE_SLOTS as u64 - 1) * TABLE_SIZE * std::mem::size_of::<u32>() as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let entry = |binding, visibility| BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Rule data bind group layout"),
+            entries: &[
+                entry(0, ShaderStages::COMPUTE),
+                entry(
+                    1,
+                    ShaderStages::COMPUTE | ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                ),
+                entry(2, ShaderStages::COMPUTE),
+            ],
+        });
+
+        let bind_group =
+            Self::create_bind_group(&buffer, &map, &regions, &bind_group_layout, device);
+
+        Self {
+            buffer,
+            map,
+            regions,
+
+            bind_group,
+            bind_group_layout,
+        }
+    }
+
+    fn create_data_buffer(data: &[u32], device: &Device) -> Buffer {
+        // Binding can't be empty
+        let data = if data.is_empty() { &[0] } else { data };
+
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Rule data buffer"),
+            contents: bytemuck::cast_slice(data),
+            usage: BufferUsages::STORAGE,
+        })
+    }
+
+    fn create_bind_group(
+        buffer: &Buffer,
+        map: &Buffer,
+        regions: &Buffer,
+        layout: &BindGroupLayout,
+        device: &Device,
+    ) -> BindGroup {
+        use wgpu::BindGroupEntry;
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Rule data bind group"),
+            layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: map.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: regions.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Replaces data of the main rule
+    pub fn set_data(&mut self, data: &[u32], device: &Device) {
+        self.buffer.destroy();
+        self.buffer = Self::create_data_buffer(data, device);
+        self.bind_group = Self::create_bind_group(
+            &self.buffer,
+            &self.map,
+            &self.regions,
+            &self.bind_group_layout,
+            device,
+        );
+    }
+
+    /// Sets lookup table of rule in slot, slot 0 is reserved for the main rule
+    pub fn set_region_rule(&self, slot: u32, table: &[u32], queue: &Queue) {
+        assert!(slot > 0 && slot < RULE_SLOTS, "Invalid region rule slot");
+        let offset = (slot as u64 - 1) * TABLE_SIZE * std::mem::size_of::<u32>() as u64;
+        queue.write_buffer(&self.regions, offset, bytemuck::cast_slice(table));
+    }
+
+    /// Reads rule slots of all cells back from GPU, blocks until they are available
+    pub fn read_map(&self, device: &Device, queue: &Queue) -> Vec<u32> {
+        super::read_buffer(&self.map, device, queue)
+    }
+
+    /// Sets rule slots of consecutive cells starting from index
+    pub fn set_slots(&self, idx: usize, slots: &[u32], queue: &Queue) {
+        let offset = idx * std::mem::size_of::<u32>();
+        queue.write_buffer(&self.map, offset as u64, bytemuck::cast_slice(slots));
+    }
+}
+
+impl HaveBindGroup for RuleData {
+    fn get_bind_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    fn get_bind(&self) -> &BindGroup {
+        &self.bind_group
+    }
+}
+
+impl BindableToComputePass for RuleData {}
+impl BinableToRenderPass for RuleData {}
+
+impl Drop for RuleData {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+        self.map.destroy();
+        self.regions.destroy();
+    }
+}
@@ -0,0 +1,191 @@
+//! Pyramid of alive cell density for drawing zoomed out field.
+//!
+//! Level `n` has a cell for each `2^n`x`2^n` cells of field holding share of alive cells among
+//! them. Levels from 1 are stored one after another in a single buffer, level 0 is the field.
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    include_wgsl, util::DeviceExt, BindGroup, Buffer, BufferUsages, CommandEncoder,
+    ComputePassDescriptor, ComputePipeline, Device,
+};
+
+use crate::{
+    bindable::{BindableToComputePass, HaveBindGroup, HaveBuffer},
+    life::Life,
+};
+
+/// Size of workgroup of density shader in both dimensions
+const WORKGROUP_SIZE: u32 = 8;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct LevelRaw {
+    src_offset: u32,
+    src_width: u32,
+    src_height: u32,
+    dst_offset: u32,
+    dst_width: u32,
+    dst_height: u32,
+    from_field: u32,
+    _padding: u32,
+}
+
+struct Level {
+    width: u32,
+    height: u32,
+    uniform: Buffer,
+    bind_group: BindGroup,
+}
+
+pub struct DensityPyramid {
+    pipeline: ComputePipeline,
+
+    buffer: Buffer,
+    levels: Vec<Level>,
+}
+
+impl DensityPyramid {
+    pub fn new(life: &Life, device: &Device) -> Self {
+        use wgpu::{BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType};
+
+        // Sizes of levels from 1 until level of a single cell
+        let (mut width, mut height) = (life.width(), life.height());
+        let mut sizes = Vec::new();
+        while width > 1 || height > 1 {
+            width = width.div_ceil(2);
+            height = height.div_ceil(2);
+            sizes.push((width, height));
+        }
+
+        let total: u32 = sizes.iter().map(|(w, h)| w * h).sum();
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Density buffer"),
+            size: (total.max(1) as usize * std::mem::size_of::<f32>()) as u64,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let entry = |binding, ty| BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Density level bind group layout"),
+            entries: &[
+                entry(0, wgpu::BufferBindingType::Uniform),
+                entry(1, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+        });
+
+        let mut levels = Vec::new();
+        let (mut src_offset, mut dst_offset) = (0, 0);
+        let (mut src_width, mut src_height) = (life.width(), life.height());
+        for (i, &(width, height)) in sizes.iter().enumerate() {
+            let raw = LevelRaw {
+                src_offset,
+                src_width,
+                src_height,
+                dst_offset,
+                dst_width: width,
+                dst_height: height,
+                from_field: (i == 0) as u32,
+                _padding: 0,
+            };
+
+            let uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Density level buffer"),
+                contents: bytemuck::cast_slice(&[raw]),
+                usage: BufferUsages::UNIFORM,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Density level bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: uniform.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+            levels.push(Level {
+                width,
+                height,
+                uniform,
+                bind_group,
+            });
+
+            src_offset = dst_offset;
+            dst_offset += width * height;
+            (src_width, src_height) = (width, height);
+        }
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Density pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout, life.life_buffer().get_bind_layout()],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Density pipeline"),
+            layout: Some(&layout),
+            module: &device.create_shader_module(include_wgsl!("../shaders/density.wgsl")),
+            entry_point: "main",
+        });
+
+        Self {
+            pipeline,
+
+            buffer,
+            levels,
+        }
+    }
+
+    /// Number of levels above the field
+    pub fn levels(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    /// Builds levels from current state of field, each level from the previous one
+    pub fn build(&self, life: &Life, encoder: &mut CommandEncoder) {
+        let life_buffer = life.life_buffer();
+
+        for level in &self.levels {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("Density pass"),
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &level.bind_group, &[]);
+            life_buffer.bind_to_compute_pass(&mut compute_pass, 1, &[]);
+            compute_pass.dispatch_workgroups(
+                level.width.div_ceil(WORKGROUP_SIZE),
+                level.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+    }
+}
+
+impl HaveBuffer for DensityPyramid {
+    fn get_buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+impl Drop for DensityPyramid {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+        for level in &self.levels {
+            level.uniform.destroy();
+        }
+    }
+}
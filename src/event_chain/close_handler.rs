@@ -19,13 +19,13 @@ impl EventChainElement for CloseHandler {
         control_flow: &mut ControlFlow,
     ) -> bool {
         match event {
-            Event::WindowEvent { window_id, event } if window.id() == *window_id => match event {
-                WindowEvent::CloseRequested => {
-                    control_flow.set_exit();
-                    true
-                }
-                _ => false,
-            },
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CloseRequested,
+            } if window.id() == *window_id => {
+                control_flow.set_exit();
+                true
+            }
             _ => false,
         }
     }
@@ -0,0 +1,57 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use winit::dpi::PhysicalPosition;
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use super::{ControlFlow, Event, EventChainElement};
+
+pub trait DropHandlerSubscriber {
+    /// File is dropped on window, `position` is cursor's position if it's known
+    fn file_dropped(&mut self, path: &Path, position: Option<PhysicalPosition<f64>>);
+}
+
+/// Handles files dropped on window. Must be before [`super::MouseHandler`] to track cursor.
+pub struct DropHandler<S: DropHandlerSubscriber> {
+    position: Option<PhysicalPosition<f64>>,
+    subscriber: Arc<Mutex<S>>,
+}
+
+impl<S: DropHandlerSubscriber> DropHandler<S> {
+    pub fn new(subscriber: Arc<Mutex<S>>) -> Self {
+        Self {
+            position: None,
+            subscriber,
+        }
+    }
+
+    fn subscriber<'s>(&'s self) -> MutexGuard<'s, S> {
+        self.subscriber.lock().unwrap()
+    }
+}
+
+impl<S: DropHandlerSubscriber> EventChainElement for DropHandler<S> {
+    fn handle(&mut self, event: &Event, window: &mut Window, _: &mut ControlFlow) -> bool {
+        match event {
+            Event::WindowEvent { window_id, event } if window.id() == *window_id => match event {
+                // Cursor is tracked for other handlers too
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.position = Some(*position);
+                    false
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    self.position = None;
+                    false
+                }
+                WindowEvent::DroppedFile(path) => {
+                    self.subscriber().file_dropped(path, self.position);
+                    true
+                }
+                _ => false,
+            },
+
+            _ => false,
+        }
+    }
+}
@@ -8,7 +8,12 @@ use winit::window::Window;
 use super::{ControlFlow, Event, EventChainElement};
 
 pub trait KeyboardHandlerSubscriber {
+    /// Called on press and on each repeat while key is held
     fn key_pressed(&mut self, key_code: &VirtualKeyCode);
+    /// Called on release
+    fn key_released(&mut self, key_code: &VirtualKeyCode);
+    /// Called when window loses focus, keys held then are released without events
+    fn focus_lost(&mut self);
 }
 
 pub struct KeyboardHandler<S: KeyboardHandlerSubscriber> {
@@ -28,26 +33,38 @@ impl<S: KeyboardHandlerSubscriber> KeyboardHandler<S> {
 impl<S: KeyboardHandlerSubscriber> EventChainElement for KeyboardHandler<S> {
     fn handle(&mut self, event: &Event, window: &mut Window, _: &mut ControlFlow) -> bool {
         match event {
-            Event::WindowEvent { window_id, event } if window.id() == *window_id => match event {
-                WindowEvent::KeyboardInput {
-                    input:
-                        KeyboardInput {
-                            state: ElementState::Pressed,
-                            virtual_keycode,
-                            scancode,
-                            ..
-                        },
-                    ..
-                } => {
-                    if let Some(k) = virtual_keycode {
-                        self.subscriber().key_pressed(k);
-                    } else {
-                        info!("Ignoring button scancode: {}", scancode);
+            Event::WindowEvent {
+                window_id,
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state,
+                                virtual_keycode,
+                                scancode,
+                                ..
+                            },
+                        ..
+                    },
+            } if window.id() == *window_id => {
+                match (virtual_keycode, state) {
+                    (Some(k), ElementState::Pressed) => self.subscriber().key_pressed(k),
+                    (Some(k), ElementState::Released) => self.subscriber().key_released(k),
+                    (None, ElementState::Pressed) => {
+                        info!("Ignoring button scancode: {}", scancode)
                     }
-                    true
+                    (None, ElementState::Released) => {}
                 }
-                _ => false,
-            },
+                true
+            }
+            // Releases are not received without focus
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Focused(false),
+            } if window.id() == *window_id => {
+                self.subscriber().focus_lost();
+                false
+            }
             _ => false,
         }
     }
@@ -10,6 +10,10 @@ mod draw_handler;
 pub use draw_handler::DrawHandler;
 pub use draw_handler::DrawHandlerSubscriber;
 
+mod drop_handler;
+pub use drop_handler::DropHandler;
+pub use drop_handler::DropHandlerSubscriber;
+
 mod keyboard_handler;
 pub use keyboard_handler::KeyboardHandler;
 pub use keyboard_handler::KeyboardHandlerSubscriber;
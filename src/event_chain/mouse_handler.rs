@@ -1,18 +1,32 @@
 use std::sync::{Arc, Mutex, MutexGuard};
 
 use winit::dpi::PhysicalPosition;
-use winit::event::ElementState;
+use winit::event::{ElementState, MouseScrollDelta};
 use winit::event::{MouseButton, WindowEvent};
 use winit::window::Window;
 
 use super::{ControlFlow, Event, EventChainElement};
 
+/// Pixels of touchpad scroll counted as one line of wheel
+const PIXELS_PER_LINE: f64 = 40.0;
+
 pub trait MouseHandlerSubscriber {
     fn clicked(&mut self, position: PhysicalPosition<f64>);
+    fn moved(&mut self, position: PhysicalPosition<f64>);
+    /// Wheel is scrolled by `lines`, positive away from user
+    fn scrolled(&mut self, position: PhysicalPosition<f64>, lines: f32);
+    /// Middle or right button is pressed
+    fn drag_started(&mut self, position: PhysicalPosition<f64>);
+    /// Cursor is moved with dragging button held
+    fn dragged(&mut self, position: PhysicalPosition<f64>);
+    /// Dragging button is released
+    fn drag_ended(&mut self, position: PhysicalPosition<f64>);
 }
 
 pub struct MouseHandler<S: MouseHandlerSubscriber> {
     position: PhysicalPosition<f64>,
+    /// Button held while dragging
+    drag_button: Option<MouseButton>,
     subscriber: Arc<Mutex<S>>,
 }
 
@@ -22,6 +36,7 @@ impl<S: MouseHandlerSubscriber> MouseHandler<S> {
 
         Self {
             subscriber,
+            drag_button: None,
             position,
         }
     }
@@ -36,7 +51,12 @@ impl<S: MouseHandlerSubscriber> EventChainElement for MouseHandler<S> {
         match event {
             Event::WindowEvent { window_id, event } if window.id() == *window_id => match event {
                 WindowEvent::CursorMoved { position, .. } => {
-                    self.position = position.clone();
+                    self.position = *position;
+                    let mut subscriber = self.subscriber();
+                    subscriber.moved(self.position);
+                    if self.drag_button.is_some() {
+                        subscriber.dragged(self.position);
+                    }
                     true
                 }
                 WindowEvent::MouseInput {
@@ -47,6 +67,32 @@ impl<S: MouseHandlerSubscriber> EventChainElement for MouseHandler<S> {
                     self.subscriber().clicked(self.position);
                     true
                 }
+                WindowEvent::MouseInput {
+                    state,
+                    button: button @ (MouseButton::Middle | MouseButton::Right),
+                    ..
+                } => {
+                    match state {
+                        ElementState::Pressed if self.drag_button.is_none() => {
+                            self.drag_button = Some(*button);
+                            self.subscriber().drag_started(self.position);
+                        }
+                        ElementState::Released if self.drag_button == Some(*button) => {
+                            self.drag_button = None;
+                            self.subscriber().drag_ended(self.position);
+                        }
+                        _ => {}
+                    }
+                    true
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let lines = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        MouseScrollDelta::PixelDelta(pixels) => (pixels.y / PIXELS_PER_LINE) as f32,
+                    };
+                    self.subscriber().scrolled(self.position, lines);
+                    true
+                }
                 _ => false,
             },
 
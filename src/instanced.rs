@@ -0,0 +1,126 @@
+//! Drawing of cells as instanced quads.
+//!
+//! Compute pass culls cells against view box of camera and writes indices of alive or
+//! tinted cells with arguments of indirect draw, so no instances are built on CPU.
+
+use wgpu::{
+    CommandEncoder, ComputePassDescriptor, ComputePipeline, Device, Queue, RenderPass,
+    RenderPipeline, TextureFormat,
+};
+
+use crate::{
+    bindable::{
+        BindableToComputePass, BindableToVertexBuffers, CellInstances, FieldView, HaveBindGroup,
+        Palette,
+    },
+    life::Life,
+    model::{Model, Quad},
+    shader::{self, Shader},
+};
+
+/// Size of workgroup of culling shader in both dimensions
+const WORKGROUP_SIZE: u32 = 8;
+
+pub struct InstancedRenderer {
+    cull_pipeline: ComputePipeline,
+    render_pipeline: RenderPipeline,
+
+    quad: Quad,
+    instances: CellInstances,
+}
+
+impl InstancedRenderer {
+    pub fn new(
+        life: &Life,
+        palette: &Palette,
+        field_view: &FieldView,
+        device: &Device,
+        format: TextureFormat,
+    ) -> Self {
+        let quad = Quad::new(device);
+        let instances = CellInstances::new(life.width() * life.height(), Quad::INDEX_COUNT, device);
+
+        let cull_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cull pipeline layout"),
+            bind_group_layouts: &[
+                field_view.get_bind_layout(),
+                life.life_buffer().get_bind_layout(),
+                instances.get_bind_layout(),
+                life.rule_data().get_bind_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+        let cull_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Cull pipeline"),
+            layout: Some(&cull_layout),
+            module: &shader::field_module(
+                "cull.wgsl",
+                include_str!("../shaders/cull.wgsl"),
+                device,
+            ),
+            entry_point: "main",
+        });
+
+        let shader = Shader::cells(device, format);
+        let render_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Cells pipeline layout"),
+            bind_group_layouts: &[
+                field_view.get_bind_layout(),
+                life.life_buffer().get_bind_layout(),
+                palette.get_bind_layout(),
+                life.rule_data().get_bind_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = shader.create_pipeline(&render_layout, device);
+
+        Self {
+            cull_pipeline,
+            render_pipeline,
+
+            quad,
+            instances,
+        }
+    }
+
+    /// Collects cells in view, must be encoded before drawing
+    pub fn cull(
+        &self,
+        life: &Life,
+        field_view: &FieldView,
+        encoder: &mut CommandEncoder,
+        queue: &Queue,
+    ) {
+        self.instances.reset(queue);
+
+        let (_, _, count_x, count_y) = field_view.view_cells();
+        if count_x == 0 || count_y == 0 {
+            return;
+        }
+
+        let life_buffer = life.life_buffer();
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("Cull pass"),
+        });
+        compute_pass.set_pipeline(&self.cull_pipeline);
+        field_view.bind_to_compute_pass(&mut compute_pass, 0, &[]);
+        life_buffer.bind_to_compute_pass(&mut compute_pass, 1, &[]);
+        self.instances
+            .bind_to_compute_pass(&mut compute_pass, 2, &[]);
+        life.rule_data()
+            .bind_to_compute_pass(&mut compute_pass, 3, &[]);
+        compute_pass.dispatch_workgroups(
+            count_x.div_ceil(WORKGROUP_SIZE),
+            count_y.div_ceil(WORKGROUP_SIZE),
+            1,
+        );
+    }
+
+    /// Draws background of field and culled cells, other bind groups are as for field shader
+    pub fn draw<'pass, 'my: 'pass>(&'my self, render_pass: &mut RenderPass<'pass>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        self.instances.bind_vertex_to_render_pass(render_pass, 1);
+        self.quad
+            .draw_indirect(render_pass, self.instances.indirect_buffer());
+    }
+}
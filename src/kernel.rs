@@ -0,0 +1,161 @@
+use anyhow::{anyhow, bail};
+use naga::{
+    valid::{Capabilities, ValidationFlags, Validator},
+    Module, ScalarKind, TypeInner,
+};
+
+use crate::rule::Rule;
+
+/// Compute kernel template, expects `evaluate` function at `EVALUATE_MARKER`
+const TEMPLATE: &str = include_str!("../shaders/life.wgsl");
+
+const EVALUATE_MARKER: &str = "// @evaluate";
+
+/// `evaluate` calling user's `transition` function
+const TRANSITION_ADAPTER: &str = include_str!("../shaders/transition.wgsl");
+
+/// `evaluate` looking up rule table
+const TABLE_ADAPTER: &str = include_str!("../shaders/table.wgsl");
+
+/// `evaluate` walking rule tree
+const TREE_ADAPTER: &str = include_str!("../shaders/tree.wgsl");
+
+const TRANSITION_SIGNATURE: &str = "fn transition(state: u32, neighbors: u32) -> u32";
+
+/// Builds kernel source for rule and validates it
+pub fn build(rule: &Rule) -> anyhow::Result<String> {
+    match rule {
+        Rule::Transition { name, source } => build_transition(source, name),
+        Rule::Table(_) => build_adapter(TABLE_ADAPTER, "table.wgsl"),
+        Rule::Tree(_) => build_adapter(TREE_ADAPTER, "tree.wgsl"),
+    }
+}
+
+fn build_adapter(adapter: &str, name: &str) -> anyhow::Result<String> {
+    let source = TEMPLATE.replacen(EVALUATE_MARKER, adapter, 1);
+    validate(&source, name)?;
+
+    Ok(source)
+}
+
+/// Splices `transition` into the kernel template and validates result.
+///
+/// `name` is used in error messages to refer to the source of transition.
+/// The transition is placed first so line numbers in errors match user's file.
+fn build_transition(transition: &str, name: &str) -> anyhow::Result<String> {
+    // Check transition alone first, so errors don't point into the template
+    let module = parse(transition, name)?;
+    check_transition(&module, name)?;
+
+    let kernel = TEMPLATE.replacen(EVALUATE_MARKER, TRANSITION_ADAPTER, 1);
+    let source = format!("{}\n{}", transition, kernel);
+    validate(&source, name)?;
+
+    Ok(source)
+}
+
+fn parse(source: &str, name: &str) -> anyhow::Result<Module> {
+    naga::front::wgsl::parse_str(source)
+        .map_err(|e| anyhow!("{}", e.emit_to_string_with_path(source, name)))
+}
+
+fn validate(source: &str, name: &str) -> anyhow::Result<()> {
+    let module = parse(source, name)?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| anyhow!("{}", e.emit_to_string_with_path(source, name)))?;
+
+    Ok(())
+}
+
+fn check_transition(module: &Module, name: &str) -> anyhow::Result<()> {
+    let is_u32 = |ty| {
+        matches!(
+            module.types[ty].inner,
+            TypeInner::Scalar {
+                kind: ScalarKind::Uint,
+                width: 4
+            }
+        )
+    };
+
+    let function = module
+        .functions
+        .iter()
+        .map(|(_, f)| f)
+        .find(|f| f.name.as_deref() == Some("transition"));
+
+    let function = match function {
+        Some(f) => f,
+        None => bail!("{}: rule must define `{}`", name, TRANSITION_SIGNATURE),
+    };
+
+    let arguments_ok =
+        function.arguments.len() == 2 && function.arguments.iter().all(|a| is_u32(a.ty));
+    let result_ok = function.result.as_ref().is_some_and(|r| is_u32(r.ty));
+
+    if !arguments_ok || !result_ok {
+        bail!(
+            "{}: `transition` has wrong signature, expected `{}`",
+            name,
+            TRANSITION_SIGNATURE
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule::{RuleTable, RuleTree};
+
+    const CONWAY: &str = include_str!("../rules/conway.wgsl");
+
+    fn transition(source: &str) -> Rule {
+        Rule::Transition {
+            name: "test.wgsl".into(),
+            source: source.into(),
+        }
+    }
+
+    #[test]
+    fn splices_adapters_at_marker() {
+        let table = build(&Rule::Table(RuleTable::parse("B3/S23").unwrap())).unwrap();
+        assert!(table.contains(TABLE_ADAPTER) && !table.contains(EVALUATE_MARKER));
+
+        let rule = "@RULE Test\n@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:permute\n\
+            0,1,1,1,0,0,0,0,0,1\n";
+        let tree = build(&Rule::Tree(RuleTree::parse(rule).unwrap())).unwrap();
+        assert!(tree.contains(TREE_ADAPTER) && !tree.contains(EVALUATE_MARKER));
+
+        let conway = build(&transition(CONWAY)).unwrap();
+        assert!(conway.starts_with(CONWAY));
+        assert!(conway.contains(TRANSITION_ADAPTER) && !conway.contains(EVALUATE_MARKER));
+    }
+
+    #[test]
+    fn rejects_wrong_transition_signature() {
+        let missing = build(&transition("fn step(state: u32) -> u32 { return state; }"));
+        assert!(missing.unwrap_err().to_string().contains("must define"));
+
+        for source in [
+            "fn transition(state: u32) -> u32 { return state; }",
+            "fn transition(state: u32, neighbors: u32) -> f32 { return 0.0; }",
+            "fn transition(state: u32, neighbors: i32) -> u32 { return state; }",
+            "fn transition(state: u32, neighbors: u32) {}",
+        ] {
+            let error = build(&transition(source)).unwrap_err().to_string();
+            assert!(error.contains("wrong signature"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn reports_errors_with_name_of_source() {
+        let error = build(&transition("fn transition("))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("test.wgsl"), "{}", error);
+    }
+}
@@ -16,6 +16,32 @@ mod bindable;
 
 mod text;
 
+mod kernel;
+
+mod rule;
+
+mod pattern;
+
+mod session;
+
+mod recorder;
+
+mod stamp;
+
+mod theme;
+
+mod instanced;
+
+mod density;
+
+mod renderer;
+
+mod offscreen;
+
+mod screenshot;
+
+mod minimap;
+
 pub async fn run() {
     env_logger::init();
 
@@ -39,6 +65,7 @@ pub async fn run() {
         Box::new(CloseHandler::new()),
         Box::new(DrawHandler::new(app.clone())),
         Box::new(KeyboardHandler::new(app.clone())),
+        Box::new(DropHandler::new(app.clone())),
         Box::new(MouseHandler::new(app.clone())),
     ];
 
@@ -1,13 +1,19 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
-use glam::Vec2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use wgpu::{
-    include_wgsl, CommandEncoderDescriptor, ComputePassDescriptor, ComputePipeline, Device, Queue,
+    include_wgsl, CommandEncoderDescriptor, ComputePassDescriptor, ComputePipeline, Device,
+    PipelineLayout, Queue, ShaderModuleDescriptor, ShaderSource,
 };
 
-use crate::bindable::{
-    BindableToComputePass, CellPos, CellPosInstances, FieldInfo, FieldState, HaveBindGroup,
-    HaveBuffer,
+use crate::{
+    bindable::{
+        BindableToComputePass, Boundary, FieldInfo, FieldState, HaveBindGroup, HaveBuffer,
+        RuleData, RULE_SLOTS,
+    },
+    kernel,
+    pattern::Pattern,
+    rule::{Rule, RuleTable},
 };
 
 type CellType = u32;
@@ -15,58 +21,221 @@ type CellType = u32;
 pub struct Life {
     field_info: FieldInfo,
 
+    tiles_pipeline: ComputePipeline,
+    pipeline_layout: PipelineLayout,
     compute_pipeline: ComputePipeline,
 
+    rule: Rule,
+    rule_data: RuleData,
+    region_rules: Vec<RuleTable>,
+
     life: Arc<FieldState>,
     new_life: FieldState,
+
+    generation: u64,
 }
 
 impl Life {
-    pub fn new(width: u32, height: u32, device: &Device) -> Self {
+    /// Creates field filled with random soup, same seed gives same soup
+    pub fn new(width: u32, height: u32, seed: u64, device: &Device) -> Self {
         // Field Info buffer prepare
         let field_info = FieldInfo::new(width, height, device);
         let field_info_bind_layout = field_info.get_bind_layout();
+        assert!(
+            field_info.tile_count() <= device.limits().max_compute_workgroups_per_dimension,
+            "Field is too large"
+        );
 
         // Current Field State init
+        let mut rng = StdRng::seed_from_u64(seed);
         let field = (0..(width * height))
-            .map(|_| rand::random::<CellType>() % 2)
+            .map(|_| rng.gen::<CellType>() % 2)
             .collect::<Vec<_>>();
 
         let life = Arc::new(FieldState::new(&field, device, true));
         let new_life = FieldState::new(&field, device, false);
 
-        // Init Compute pipiline
-        let module = device.create_shader_module(include_wgsl!("../shaders/life.wgsl"));
+        // Rule prepare
+        let table = RuleTable::conway();
+        let rule_data = RuleData::new(table.table(), (width * height) as usize, device);
+        let rule = Rule::Table(table);
+
+        // Init active tiles pipeline
+        let tiles_module = device.create_shader_module(include_wgsl!("../shaders/tiles.wgsl"));
+        let tiles_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Active tiles pipeline layout"),
+            bind_group_layouts: &[field_info_bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let tiles_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Active tiles pipeline"),
+            layout: Some(&tiles_layout),
+            module: &tiles_module,
+            entry_point: "main",
+        });
 
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        // Init Compute pipiline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Life Compute pipeline layout"),
             bind_group_layouts: &[
-                &field_info_bind_layout,    // Group 0
-                life.get_bind_layout(),     // Group 1
-                new_life.get_bind_layout(), // Group 2
+                field_info_bind_layout,      // Group 0
+                life.get_bind_layout(),      // Group 1
+                new_life.get_bind_layout(),  // Group 2
+                rule_data.get_bind_layout(), // Group 3
             ],
             push_constant_ranges: &[],
         });
 
-        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Life Compute pipeline"),
-            layout: Some(&layout),
-            module: &module,
-            entry_point: "main",
-        });
+        let source = kernel::build(&rule).expect("Default rule must compile");
+        let compute_pipeline = Self::create_pipeline(&source, &pipeline_layout, device);
 
         Self {
             field_info,
 
+            tiles_pipeline,
+            pipeline_layout,
             compute_pipeline,
 
+            rule,
+            rule_data,
+            region_rules: Vec::new(),
+
             life,
             new_life,
+
+            generation: 0,
+        }
+    }
+
+    fn create_pipeline(source: &str, layout: &PipelineLayout, device: &Device) -> ComputePipeline {
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Life kernel"),
+            source: ShaderSource::Wgsl(source.into()),
+        });
+
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Life Compute pipeline"),
+            layout: Some(layout),
+            module: &module,
+            entry_point: "main",
+        })
+    }
+
+    /// Replaces rule of simulation, on error current rule is kept
+    pub fn set_rule(&mut self, rule: Rule, queue: &Queue, device: &Device) -> anyhow::Result<()> {
+        let source = kernel::build(&rule)?;
+        if let Some(data) = rule.data() {
+            self.rule_data.set_data(data, device);
+        }
+
+        self.compute_pipeline = Self::create_pipeline(&source, &self.pipeline_layout, device);
+        self.rule = rule;
+
+        // Stable regions of old rule may change with the new one
+        self.field_info.mark_all_changed(queue);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Adds rule which can be painted on rule map, returns its slot
+    pub fn add_region_rule(&mut self, rule: RuleTable, queue: &Queue) -> anyhow::Result<u32> {
+        let slot = self.region_rules.len() as u32 + 1;
+        if slot >= RULE_SLOTS {
+            anyhow::bail!("At most {} region rules are supported", RULE_SLOTS - 1);
+        }
+
+        self.rule_data.set_region_rule(slot, rule.table(), queue);
+        self.region_rules.push(rule);
+
+        Ok(slot)
+    }
+
+    /// Replaces all region rules, rule map is kept and must not use removed slots
+    pub fn set_region_rules(&mut self, rules: Vec<RuleTable>, queue: &Queue) -> anyhow::Result<()> {
+        if rules.len() >= RULE_SLOTS as usize {
+            anyhow::bail!("At most {} region rules are supported", RULE_SLOTS - 1);
+        }
+
+        for (slot, rule) in (1..).zip(&rules) {
+            self.rule_data.set_region_rule(slot, rule.table(), queue);
+        }
+        self.region_rules = rules;
+        self.field_info.mark_all_changed(queue);
+
+        Ok(())
+    }
+
+    /// Rules of slots from 1, slot 0 is the main rule
+    #[inline(always)]
+    pub fn region_rules(&self) -> &[RuleTable] {
+        &self.region_rules
+    }
+
+    /// Sets which rule rectangle of cells uses, slot 0 is the main rule.
+    ///
+    /// Rectangle has bottom left corner at cell and wraps around field.
+    pub fn set_rule_region(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        slot: u32,
+        queue: &Queue,
+    ) {
+        assert!(slot as usize <= self.region_rules.len(), "Empty rule slot");
+        let field_width = self.field_info.width();
+        let width = width.min(field_width);
+        let height = height.min(self.field_info.height());
+        let (x, y) = (x % field_width, y % self.field_info.height());
+
+        let slots = vec![slot; width as usize];
+        for j in 0..height {
+            let start = self.index(x, y + j);
+
+            // Part of row which wraps around is written at its start
+            let first = ((field_width - x) as usize).min(slots.len());
+            for (idx, run) in [
+                (start, &slots[..first]),
+                (start - x as usize, &slots[first..]),
+            ] {
+                if !run.is_empty() {
+                    self.rule_data.set_slots(idx, run, queue);
+                }
+            }
         }
+
+        self.field_info
+            .mark_region_changed(x, y, width, height, queue);
+    }
+
+    #[inline(always)]
+    pub fn rule_data(&self) -> &RuleData {
+        &self.rule_data
     }
 
     pub fn step(&mut self, queue: &Queue, device: &Device) {
         let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+        self.field_info.reset_active(queue);
+
+        {
+            let mut compute_pass =
+                encoder.begin_compute_pass(&ComputePassDescriptor { label: None });
+            compute_pass.set_pipeline(&self.tiles_pipeline);
+            self.field_info
+                .bind_to_compute_pass(&mut compute_pass, 0, &[]);
+
+            let workgroups = self.field_info.tile_count().div_ceil(64);
+            compute_pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+
+        self.field_info.prepare_dispatch(&mut encoder);
 
         {
             let mut compute_pass =
@@ -78,13 +247,41 @@ impl Life {
             self.life.bind_to_compute_pass(&mut compute_pass, 1, &[]);
             self.new_life
                 .bind_to_compute_pass(&mut compute_pass, 2, &[]);
+            self.rule_data
+                .bind_to_compute_pass(&mut compute_pass, 3, &[]);
 
-            compute_pass.dispatch_workgroups(self.field_info.width(), self.field_info.height(), 1)
+            compute_pass.dispatch_workgroups_indirect(self.field_info.dispatch_buffer(), 0);
         }
 
         // Copy result
         self.life.copy_from(&self.new_life, &mut encoder);
+        self.field_info.swap_changed(&mut encoder);
         queue.submit(Some(encoder.finish()));
+
+        self.generation += 1;
+    }
+
+    /// Number of steps made since start
+    #[inline(always)]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    #[inline(always)]
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+
+    #[inline(always)]
+    pub fn boundary(&self) -> Boundary {
+        self.field_info.boundary()
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary, queue: &Queue) {
+        self.field_info.set_boundary(boundary, queue);
+
+        // Cells at edges have other neighbours now
+        self.field_info.mark_all_changed(queue);
     }
 
     #[inline(always)]
@@ -92,6 +289,16 @@ impl Life {
         (self.field_info.width() * self.field_info.height()) as usize
     }
 
+    #[inline(always)]
+    pub fn width(&self) -> u32 {
+        self.field_info.width()
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> u32 {
+        self.field_info.height()
+    }
+
     #[inline(always)]
     fn index(&self, x: u32, y: u32) -> usize {
         let x_rem = x % self.field_info.width();
@@ -100,35 +307,6 @@ impl Life {
         x_rem as usize + y_rem as usize * self.field_info.width() as usize
     }
 
-    pub fn generate_cell_info(&self, view_box: (Vec2, Vec2), device: &Device) -> CellPosInstances {
-        let mut positions = Vec::with_capacity(self.cell_count() as usize);
-
-        let min_x = view_box.0.x.floor().max(0.0) as u32;
-        let max_x = view_box
-            .1
-            .x
-            .floor()
-            .min((self.field_info.width() - 1) as f32) as u32;
-
-        let min_y = view_box.0.y.floor().max(0.0) as u32;
-        let max_y = view_box
-            .1
-            .y
-            .floor()
-            .min((self.field_info.height() - 1) as f32) as u32;
-
-        for i in min_x..=max_x {
-            for j in min_y..=max_y {
-                positions.push(CellPos {
-                    pos: [i as f32, j as f32],
-                    idx: self.index(i, j) as u32,
-                })
-            }
-        }
-
-        CellPosInstances::new(positions, device)
-    }
-
     pub fn set_cell(&mut self, x: u32, y: u32, queue: &Queue) {
         let idx = self.index(x, y);
         let offset = idx * std::mem::size_of::<CellType>();
@@ -144,6 +322,157 @@ impl Life {
             offset as u64,
             bytemuck::cast_slice(&[cell]),
         );
+
+        self.field_info.mark_changed(
+            x % self.field_info.width(),
+            y % self.field_info.height(),
+            queue,
+        );
+    }
+
+    /// Kills all cells
+    pub fn clear(&mut self, queue: &Queue) {
+        let field = vec![0 as CellType; self.cell_count()];
+        queue.write_buffer(self.life.get_buffer(), 0, bytemuck::cast_slice(&field));
+        queue.write_buffer(self.new_life.get_buffer(), 0, bytemuck::cast_slice(&field));
+
+        // Rules with birth on 0 neighbours change empty field
+        self.field_info.mark_all_changed(queue);
+    }
+
+    /// Places pattern with its bottom left corner at cell, overwriting cells under it
+    pub fn place_pattern(
+        &mut self,
+        x: u32,
+        y: u32,
+        pattern: &Pattern,
+        queue: &Queue,
+    ) -> anyhow::Result<()> {
+        let width = self.field_info.width();
+        let height = self.field_info.height();
+        if pattern.width() > width || pattern.height() > height {
+            anyhow::bail!(
+                "Pattern {}x{} doesn't fit into field {}x{}",
+                pattern.width(),
+                pattern.height(),
+                width,
+                height
+            );
+        }
+
+        let (x, y) = (x % width, y % height);
+        for j in 0..pattern.height() {
+            // Rows of pattern go from the top, north is +y
+            let cells = pattern.row(pattern.height() - 1 - j);
+            let start = self.index(x, y + j);
+
+            // Part of row which wraps around is written at its start
+            let first = ((width - x) as usize).min(cells.len());
+            for (idx, run) in [
+                (start, &cells[..first]),
+                (start - x as usize, &cells[first..]),
+            ] {
+                if run.is_empty() {
+                    continue;
+                }
+
+                let offset = (idx * std::mem::size_of::<CellType>()) as u64;
+                queue.write_buffer(self.life.get_buffer(), offset, bytemuck::cast_slice(run));
+                queue.write_buffer(
+                    self.new_life.get_buffer(),
+                    offset,
+                    bytemuck::cast_slice(run),
+                );
+            }
+        }
+
+        self.field_info
+            .mark_region_changed(x, y, pattern.width(), pattern.height(), queue);
+
+        Ok(())
+    }
+
+    /// Reads rectangle of cells with bottom left corner at cell, rectangle wraps around field.
+    ///
+    /// Only rows of rectangle are copied from GPU, so small regions are cheap to read every frame.
+    pub fn read_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        device: &Device,
+        queue: &Queue,
+    ) -> Pattern {
+        // Only whole rows of region are read, consecutive rows in one range
+        let field_w = self.width() as usize;
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for j in 0..height {
+            let start = self.index(0, y + j);
+            match ranges.last_mut() {
+                Some(range) if range.end == start => range.end += field_w,
+                _ => ranges.push(start..start + field_w),
+            }
+        }
+        let rows = self.life.read_ranges(&ranges, device, queue);
+
+        let mut pattern = Pattern::new(width, height);
+        for j in 0..height {
+            for i in 0..width {
+                let idx = j as usize * field_w + ((x + i) % self.width()) as usize;
+                // Rows of pattern go from the top, north is +y
+                pattern.set(i, height - 1 - j, rows[idx]);
+            }
+        }
+
+        pattern
+    }
+
+    /// Reads rule slots of all cells as pattern of states
+    pub fn read_rule_map(&self, device: &Device, queue: &Queue) -> Pattern {
+        let map = self.rule_data.read_map(device, queue);
+        let (width, height) = (self.width(), self.height());
+
+        let mut pattern = Pattern::new(width, height);
+        for j in 0..height {
+            for i in 0..width {
+                // Rows of pattern go from the top, north is +y
+                pattern.set(i, height - 1 - j, map[self.index(i, j)]);
+            }
+        }
+
+        pattern
+    }
+
+    /// Replaces rule slots of all cells with states of pattern of the field's size
+    pub fn set_rule_map(&mut self, map: &Pattern, queue: &Queue) -> anyhow::Result<()> {
+        let (width, height) = (self.width(), self.height());
+        if (map.width(), map.height()) != (width, height) {
+            anyhow::bail!(
+                "Rule map {}x{} doesn't match field {}x{}",
+                map.width(),
+                map.height(),
+                width,
+                height
+            );
+        }
+
+        let slots: Vec<u32> = (0..height)
+            .rev()
+            .flat_map(|y| map.row(y))
+            .copied()
+            .collect();
+        if let Some(slot) = slots
+            .iter()
+            .find(|&&s| s as usize > self.region_rules.len())
+        {
+            anyhow::bail!("Rule map uses empty slot {}", slot);
+        }
+
+        self.rule_data.set_slots(0, &slots, queue);
+        self.field_info.mark_all_changed(queue);
+
+        Ok(())
     }
 
     #[inline(always)]
@@ -0,0 +1,135 @@
+//! Overlay in a corner of window showing the whole field and outline of the view.
+
+use glam::Vec2;
+use wgpu::{Device, Queue};
+use winit::dpi::PhysicalPosition;
+
+use crate::{
+    bindable::{Camera, FieldView},
+    density::DensityPyramid,
+    life::Life,
+};
+
+/// Distance from minimap to edges of window in pixels
+const MARGIN: u32 = 10;
+
+/// Corner of window minimap is placed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    /// Corner by name: `top-left`, `top-right`, `bottom-left` or `bottom-right`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-left" => Some(Self::TopLeft),
+            "top-right" => Some(Self::TopRight),
+            "bottom-left" => Some(Self::BottomLeft),
+            "bottom-right" => Some(Self::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+pub struct Minimap {
+    corner: Corner,
+    /// Longer side in pixels
+    size: u32,
+    /// Size of field in cells
+    field: (u32, u32),
+    /// Position of top left corner and size in pixels of window
+    rect: (u32, u32, u32, u32),
+
+    camera: Camera,
+    field_view: FieldView,
+}
+
+impl Minimap {
+    /// Creates minimap with longer side of `size` pixels in `corner` of `window`
+    pub fn new(
+        size: u32,
+        corner: Corner,
+        window: (u32, u32),
+        life: &Life,
+        density: &DensityPyramid,
+        device: &Device,
+        queue: &Queue,
+    ) -> Self {
+        let field = (life.width(), life.height());
+
+        let mut minimap = Self {
+            corner,
+            size: size.max(1),
+            field,
+            rect: (0, 0, 1, 1),
+
+            camera: Camera::new(1, 1, device),
+            field_view: FieldView::new(field.0, field.1, density, device),
+        };
+        minimap.place(window, queue);
+
+        minimap
+    }
+
+    /// Places minimap in its corner of window, shrinking it to fit
+    pub fn place(&mut self, (window_w, window_h): (u32, u32), queue: &Queue) {
+        let (field_w, field_h) = (self.field.0 as f32, self.field.1 as f32);
+        let fit = |window: u32| window.saturating_sub(2 * MARGIN).max(1) as f32;
+        let scale = (self.size as f32 / field_w.max(field_h))
+            .min(fit(window_w) / field_w)
+            .min(fit(window_h) / field_h);
+
+        let width = ((field_w * scale).round() as u32).max(1);
+        let height = ((field_h * scale).round() as u32).max(1);
+        let x = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => MARGIN,
+            Corner::TopRight | Corner::BottomRight => window_w.saturating_sub(MARGIN + width),
+        };
+        let y = match self.corner {
+            Corner::TopLeft | Corner::TopRight => MARGIN,
+            Corner::BottomLeft | Corner::BottomRight => window_h.saturating_sub(MARGIN + height),
+        };
+        // Margin is dropped in tiny windows
+        let x = x.min(window_w.saturating_sub(width));
+        let y = y.min(window_h.saturating_sub(height));
+        self.rect = (x, y, width, height);
+
+        // Cells are centred at their coordinates
+        self.camera.resize(width, height);
+        self.camera
+            .set_position(Vec2::new(field_w - 1.0, field_h - 1.0) / 2.0);
+        self.camera.set_scale_factor(width as f32 / field_w);
+        self.field_view.set_camera(&self.camera, queue);
+    }
+
+    /// Position of top left corner and size in pixels of window
+    pub fn rect(&self) -> (u32, u32, u32, u32) {
+        self.rect
+    }
+
+    /// Point of field under position in window, if it is on minimap
+    pub fn field_position(&self, position: PhysicalPosition<f64>) -> Option<Vec2> {
+        let (x, y, width, height) = self.rect;
+        let u = (position.x - x as f64) / width as f64;
+        let v = (position.y - y as f64) / height as f64;
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+
+        let clip = Vec2::new(2.0 * u as f32 - 1.0, 1.0 - 2.0 * v as f32);
+        Some(self.camera.from_clip_space_to_local(clip))
+    }
+
+    /// Outlines part of field seen by camera
+    pub fn follow(&mut self, camera: &Camera, queue: &Queue) {
+        self.field_view.set_outline(Some(camera.view_box()), queue);
+    }
+
+    pub fn field_view(&self) -> &FieldView {
+        &self.field_view
+    }
+}
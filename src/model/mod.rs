@@ -1,7 +1,7 @@
 use std::ops::Range;
 
 use bytemuck::{Pod, Zeroable};
-use wgpu::RenderPass;
+use wgpu::{Buffer, RenderPass};
 
 mod quad;
 pub use quad::Quad;
@@ -33,4 +33,11 @@ pub trait Model {
         render_pass: &mut RenderPass<'pass>,
         instances: Range<u32>,
     );
+
+    /// Draws model with instance count and other arguments of `DrawIndexedIndirect` in buffer
+    fn draw_indirect<'pass, 'my: 'pass>(
+        &'my self,
+        render_pass: &mut RenderPass<'pass>,
+        indirect_buffer: &'pass Buffer,
+    );
 }
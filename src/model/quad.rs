@@ -26,16 +26,19 @@ pub struct Quad {
 }
 
 impl Quad {
+    /// Number of indices drawn per instance
+    pub const INDEX_COUNT: u32 = INDICES.len() as u32;
+
     pub fn new(device: &Device) -> Self {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Quad vertex buffer"),
-            contents: bytemuck::cast_slice(&VERTICES),
+            contents: bytemuck::cast_slice(VERTICES),
             usage: BufferUsages::VERTEX,
         });
 
         let indices_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Quad indices buffer"),
-            contents: bytemuck::cast_slice(&INDICES),
+            contents: bytemuck::cast_slice(INDICES),
             usage: BufferUsages::INDEX,
         });
 
@@ -62,4 +65,17 @@ impl Model for Quad {
 
         render_pass.draw_indexed(0..self.num_indices, 0, instances)
     }
+
+    fn draw_indirect<'pass, 'my: 'pass>(
+        &'my self,
+        render_pass: &mut wgpu::RenderPass<'pass>,
+        indirect_buffer: &'pass Buffer,
+    ) {
+        use wgpu::IndexFormat;
+
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.indices_buffer.slice(..), IndexFormat::Uint16);
+
+        render_pass.draw_indexed_indirect(indirect_buffer, 0)
+    }
 }
@@ -0,0 +1,104 @@
+//! Texture rendered to without window and read back to memory.
+
+use wgpu::{
+    BufferUsages, CommandEncoder, Device, Extent3d, Queue, Texture, TextureFormat, TextureUsages,
+    TextureView,
+};
+
+/// Format of texture, bytes are RGBA
+pub const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+pub struct Offscreen {
+    width: u32,
+    height: u32,
+
+    texture: Texture,
+    view: TextureView,
+    staging: wgpu::Buffer,
+    /// Rows of texture are copied to staging buffer aligned
+    padded_row: u32,
+}
+
+impl Offscreen {
+    pub fn new(width: u32, height: u32, device: &Device) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen texture"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let padded_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen staging buffer"),
+            size: (padded_row * height) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            width,
+            height,
+
+            texture,
+            view,
+            staging,
+            padded_row,
+        }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    /// Submits encoder drawing to texture and reads texture back, rows of pixels go from the top
+    pub fn read(&self, mut encoder: CommandEncoder, device: &Device, queue: &Queue) -> Vec<u8> {
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.staging,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(self.padded_row),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = self.staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+
+        let pixels = {
+            let data = slice.get_mapped_range();
+            data.chunks(self.padded_row as usize)
+                .flat_map(|row| &row[..(self.width * 4) as usize])
+                .copied()
+                .collect()
+        };
+        self.staging.unmap();
+
+        pixels
+    }
+}
+
+impl Drop for Offscreen {
+    fn drop(&mut self) {
+        self.texture.destroy();
+        self.staging.destroy();
+    }
+}
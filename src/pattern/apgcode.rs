@@ -0,0 +1,331 @@
+//! Apgcodes of objects as used by Catagolue, e.g. `xs4_33` for block or `xq4_153` for glider.
+//!
+//! Code is prefix with type of object and its period, `xs` still life with population,
+//! `xp` oscillator or `xq` spaceship, followed by cells in extended Wechsler format:
+//! strips of 5 rows, each column of strip is a character of its 5 bits.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context};
+
+use super::Pattern;
+use crate::rule::RuleTable;
+
+/// Max period of object searched by encoding
+const MAX_PERIOD: usize = 1024;
+
+/// Characters of columns and lengths of zero runs
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Alive cells, `y` goes down
+type Cells = HashSet<(i64, i64)>;
+
+/// Computes apgcode of object: its period, canonical phase and orientation
+pub fn encode(pattern: &Pattern, rule: &RuleTable) -> anyhow::Result<String> {
+    let table = rule.table();
+    if table[0] > 0 {
+        bail!("Rules with B0 have no apgcodes");
+    }
+
+    let initial = cells(pattern);
+    if initial.is_empty() {
+        bail!("Object is empty");
+    }
+
+    let (first, first_origin) = normalized(&initial);
+    let mut phases = vec![initial];
+    let (period, moves) = loop {
+        let next = step(phases.last().unwrap(), table);
+        if next.is_empty() {
+            bail!("Object dies after {} generations", phases.len());
+        }
+
+        let (current, origin) = normalized(&next);
+        if current == first {
+            break (phases.len(), origin != first_origin);
+        }
+
+        if phases.len() >= MAX_PERIOD {
+            bail!("Object isn't periodic with period up to {}", MAX_PERIOD);
+        }
+        phases.push(next);
+    };
+
+    let prefix = match (period, moves) {
+        (_, true) => format!("xq{}", period),
+        (1, false) => format!("xs{}", phases[0].len()),
+        (_, false) => format!("xp{}", period),
+    };
+
+    // Shortest representation of all phases and orientations, then the first alphabetically
+    let wechsler = phases
+        .iter()
+        .flat_map(|phase| (0..8).map(move |orientation| wechsler(&oriented(phase, orientation))))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap();
+
+    Ok(format!("{}_{}", prefix, wechsler))
+}
+
+/// Decodes cells of apgcode, e.g. `xq4_153`
+pub fn decode(code: &str) -> anyhow::Result<Pattern> {
+    let code = code.trim();
+    let body = match code.split_once('_') {
+        Some((prefix, body)) if ["xs", "xp", "xq"].iter().any(|p| prefix.starts_with(p)) => body,
+        _ => bail!(
+            "Invalid apgcode `{}`, expected `xs`, `xp` or `xq` object",
+            code
+        ),
+    };
+
+    let digit = |c: u8| DIGITS.iter().position(|&d| d == c);
+
+    // Run of zeros `yz` has separator of strips `z` as its length, so body is read sequentially
+    let mut cells = Vec::new();
+    let (mut x, mut strip) = (0, 0);
+    let mut chars = body.bytes();
+
+    while let Some(c) = chars.next() {
+        match c {
+            b'z' => {
+                x = 0;
+                strip += 1;
+            }
+            b'w' => x += 2,
+            b'x' => x += 3,
+            b'y' => {
+                let run = chars
+                    .next()
+                    .and_then(digit)
+                    .with_context(|| format!("Invalid run of zeros in `{}`", code))?;
+                x += 4 + run as i64;
+            }
+            _ => {
+                let bits = digit(c)
+                    .filter(|&d| d < 32)
+                    .with_context(|| format!("Unexpected `{}` in `{}`", c as char, code))?;
+
+                for bit in (0..5).filter(|b| bits & (1 << b) != 0) {
+                    cells.push((x, strip * 5 + bit));
+                }
+                x += 1;
+            }
+        }
+    }
+
+    if cells.is_empty() {
+        bail!("Apgcode `{}` has no cells", code);
+    }
+
+    let mut pattern = Pattern::from_cells(&cells, u32::MAX, u32::MAX)?;
+    pattern.metadata.name = Some(code.to_owned());
+
+    Ok(pattern)
+}
+
+/// Object made of alive cell nearest to `x`, `y` and cells connected to it.
+///
+/// Cells are connected if they are at most 2 cells apart, so parts of object interacting
+/// with each other are kept together.
+pub fn object_near(pattern: &Pattern, x: u32, y: u32) -> Option<Pattern> {
+    let object = object_cells(pattern, x, y)?;
+
+    // Object is a part of pattern, so it fits
+    Pattern::from_cells(&object, pattern.width(), pattern.height()).ok()
+}
+
+/// Bounding box of object near `x`, `y` as `left`, `top`, `width`, `height`, see [`object_near`]
+pub fn object_box(pattern: &Pattern, x: u32, y: u32) -> Option<(u32, u32, u32, u32)> {
+    let object = object_cells(pattern, x, y)?;
+    let min_x = object.iter().map(|c| c.0).min()?;
+    let min_y = object.iter().map(|c| c.1).min()?;
+    let max_x = object.iter().map(|c| c.0).max()?;
+    let max_y = object.iter().map(|c| c.1).max()?;
+
+    Some((
+        min_x as u32,
+        min_y as u32,
+        (max_x - min_x + 1) as u32,
+        (max_y - min_y + 1) as u32,
+    ))
+}
+
+fn object_cells(pattern: &Pattern, x: u32, y: u32) -> Option<Vec<(i64, i64)>> {
+    let alive = cells(pattern);
+    let distance = |&(cx, cy): &(i64, i64)| (cx - x as i64).pow(2) + (cy - y as i64).pow(2);
+    let start = *alive.iter().min_by_key(|c| distance(c))?;
+
+    let mut object = vec![start];
+    let mut visited = HashSet::from([start]);
+    let mut next = 0;
+    while let Some(&(cx, cy)) = object.get(next) {
+        next += 1;
+
+        for dy in -2..=2 {
+            for dx in -2..=2 {
+                let cell = (cx + dx, cy + dy);
+                if alive.contains(&cell) && visited.insert(cell) {
+                    object.push(cell);
+                }
+            }
+        }
+    }
+
+    Some(object)
+}
+
+fn cells(pattern: &Pattern) -> Cells {
+    (0..pattern.height())
+        .flat_map(|y| (0..pattern.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| pattern.get(x, y) > 0)
+        .map(|(x, y)| (x as i64, y as i64))
+        .collect()
+}
+
+/// Next generation by rule's table, indexed by `NW N NE W C E SW S SE` bits
+fn step(cells: &Cells, table: &[u32]) -> Cells {
+    let mut candidates = HashMap::new();
+    for &(x, y) in cells {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                *candidates.entry((x + dx, y + dy)).or_insert(0) |= 1 << (4 + dx + 3 * dy);
+            }
+        }
+    }
+
+    // Bits of cell's neighbours are set by the alive cells around it
+    candidates
+        .into_iter()
+        .filter(|&(_, neighbourhood)| table[neighbourhood as usize] > 0)
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+/// Cells moved to have top left corner at zero, and the original corner
+fn normalized(cells: &Cells) -> (Cells, (i64, i64)) {
+    let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    let moved = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+
+    (moved, (min_x, min_y))
+}
+
+/// One of 8 rotations and reflections of cells, normalized
+fn oriented(cells: &Cells, orientation: u8) -> Cells {
+    let transformed = cells
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = if orientation & 4 != 0 { (y, x) } else { (x, y) };
+            let x = if orientation & 1 != 0 { -x } else { x };
+            let y = if orientation & 2 != 0 { -y } else { y };
+            (x, y)
+        })
+        .collect();
+
+    normalized(&transformed).0
+}
+
+/// Extended Wechsler format of normalized cells
+fn wechsler(cells: &Cells) -> String {
+    let width = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+
+    let mut strips = Vec::new();
+    for strip in 0..(height + 4) / 5 {
+        let mut columns: Vec<usize> = (0..width)
+            .map(|x| {
+                (0..5)
+                    .filter(|bit| cells.contains(&(x, strip * 5 + bit)))
+                    .map(|bit| 1 << bit)
+                    .sum()
+            })
+            .collect();
+
+        while columns.last() == Some(&0) {
+            columns.pop();
+        }
+
+        let mut out = String::new();
+        let mut zeros = 0;
+        for column in columns {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+
+            push_zeros(&mut out, zeros);
+            zeros = 0;
+            out.push(DIGITS[column] as char);
+        }
+
+        strips.push(out);
+    }
+
+    strips.join("z")
+}
+
+/// Writes run of zero columns: `0`, `w` for 2, `x` for 3 or `y` with length from 4 to 39
+fn push_zeros(out: &mut String, mut zeros: usize) {
+    while zeros >= 4 {
+        let run = zeros.min(39);
+        out.push('y');
+        out.push(DIGITS[run - 4] as char);
+        zeros -= run;
+    }
+
+    match zeros {
+        1 => out.push('0'),
+        2 => out.push('w'),
+        3 => out.push('x'),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conway() -> RuleTable {
+        RuleTable::parse("B3/S23").unwrap()
+    }
+
+    /// Rows of pattern from the top
+    fn rows(pattern: &Pattern) -> Vec<Vec<u32>> {
+        (0..pattern.height())
+            .map(|y| pattern.row(y).to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn encodes_block_and_glider() {
+        let block = Pattern::from_cells(&[(0, 0), (1, 0), (0, 1), (1, 1)], 2, 2).unwrap();
+        assert_eq!(encode(&block, &conway()).unwrap(), "xs4_33");
+
+        // Any phase and orientation of glider has the same code
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let flipped = glider.map(|(x, y)| (y, 2 - x));
+        for cells in [glider, flipped] {
+            let pattern = Pattern::from_cells(&cells, 3, 3).unwrap();
+            assert_eq!(encode(&pattern, &conway()).unwrap(), "xq4_153");
+        }
+    }
+
+    #[test]
+    fn decodes_block_and_glider() {
+        let block = decode("xs4_33").unwrap();
+        assert_eq!(rows(&block), [[1, 1], [1, 1]]);
+        assert_eq!(block.metadata().name.as_deref(), Some("xs4_33"));
+
+        let glider = decode("xq4_153").unwrap();
+        assert_eq!(rows(&glider), [[1, 1, 1], [0, 0, 1], [0, 1, 0]]);
+        assert_eq!(encode(&glider, &conway()).unwrap(), "xq4_153");
+    }
+
+    #[test]
+    fn rejects_invalid_codes() {
+        assert!(decode("xs4").is_err());
+        assert!(decode("yl144_1").is_err());
+        assert!(decode("xs0_0").is_err());
+        assert!(decode("xs4_3!").is_err());
+    }
+}
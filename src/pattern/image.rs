@@ -0,0 +1,249 @@
+//! Conversion of PNG images to patterns
+
+use std::{fs::File, path::Path};
+
+use anyhow::{bail, Context};
+
+use super::Pattern;
+
+type Rgb = [f32; 3];
+
+/// How image is converted to cells
+pub struct ImageOptions {
+    /// Size of pattern, image's size if not set. Aspect ratio is kept if only one is set
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Floyd–Steinberg dithering instead of threshold
+    pub dither: bool,
+    /// Luminance from 0 to 1 at which cells become alive
+    pub threshold: f32,
+    /// Dark pixels are alive instead of bright ones
+    pub invert: bool,
+    /// Colors of states for multi-state rules, pixels get state of the nearest color.
+    ///
+    /// Luminance is used if there are two states or less.
+    pub colors: Vec<Rgb>,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            width: None,
+            height: None,
+            dither: false,
+            threshold: 0.5,
+            invert: false,
+            colors: Vec::new(),
+        }
+    }
+}
+
+impl ImageOptions {
+    /// Parses comma separated options, e.g. `200x100,dither,invert,threshold=0.4`.
+    ///
+    /// Size is `WxH`, `Wx` or `xH`.
+    pub fn parse(options: &str) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+
+        for option in options.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+            let size = |s: &str| -> anyhow::Result<Option<u32>> {
+                match s {
+                    "" => Ok(None),
+                    _ => Ok(Some(s.parse()?)),
+                }
+            };
+
+            match option.split_once(['=', 'x']) {
+                _ if option == "dither" => result.dither = true,
+                _ if option == "invert" => result.invert = true,
+                Some(("threshold", value)) => {
+                    result.threshold = value
+                        .parse()
+                        .with_context(|| format!("Invalid threshold `{}`", value))?;
+                }
+                Some((width, height)) if option.contains('x') => {
+                    result.width =
+                        size(width).with_context(|| format!("Invalid size `{}`", option))?;
+                    result.height =
+                        size(height).with_context(|| format!("Invalid size `{}`", option))?;
+                }
+                _ => bail!("Unknown image option `{}`", option),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Loads PNG image and converts it to pattern, pattern is at most `max_width`x`max_height`
+pub fn load(
+    path: &Path,
+    options: &ImageOptions,
+    max_width: u32,
+    max_height: u32,
+) -> anyhow::Result<Pattern> {
+    let file =
+        File::open(path).with_context(|| format!("Cannot read image file {}", path.display()))?;
+
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder
+        .read_info()
+        .with_context(|| format!("Invalid PNG file {}", path.display()))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .with_context(|| format!("Invalid PNG file {}", path.display()))?;
+
+    let pixels = decode_pixels(&buffer[..info.buffer_size()], info.color_type)?;
+    let (width, height) = target_size(info.width, info.height, options, max_width, max_height);
+    let scaled = scale(&pixels, info.width, info.height, width, height);
+
+    Ok(convert(&scaled, width, height, options))
+}
+
+/// Converts 8-bit pixels to colors, transparent pixels are blended over black
+fn decode_pixels(bytes: &[u8], color_type: png::ColorType) -> anyhow::Result<Vec<Rgb>> {
+    let value = |b: u8| b as f32 / 255.0;
+
+    let pixels = match color_type {
+        png::ColorType::Grayscale => bytes.iter().map(|&l| [value(l); 3]).collect(),
+        png::ColorType::GrayscaleAlpha => bytes
+            .chunks(2)
+            .map(|p| [value(p[0]) * value(p[1]); 3])
+            .collect(),
+        png::ColorType::Rgb => bytes
+            .chunks(3)
+            .map(|p| [value(p[0]), value(p[1]), value(p[2])])
+            .collect(),
+        png::ColorType::Rgba => bytes
+            .chunks(4)
+            .map(|p| [0, 1, 2].map(|i| value(p[i]) * value(p[3])))
+            .collect(),
+        png::ColorType::Indexed => bail!("Indexed colors must be expanded"),
+    };
+
+    Ok(pixels)
+}
+
+/// Size of pattern from options, keeping aspect ratio and fitting into max size
+fn target_size(
+    width: u32,
+    height: u32,
+    options: &ImageOptions,
+    max_width: u32,
+    max_height: u32,
+) -> (u32, u32) {
+    let aspect = width as f32 / height as f32;
+    let (width, height) = match (options.width, options.height) {
+        (Some(w), Some(h)) => (w as f32, h as f32),
+        (Some(w), None) => (w as f32, w as f32 / aspect),
+        (None, Some(h)) => (h as f32 * aspect, h as f32),
+        (None, None) => (width as f32, height as f32),
+    };
+
+    let fit = (max_width as f32 / width)
+        .min(max_height as f32 / height)
+        .min(1.0);
+    let size = |s: f32| ((s * fit).round() as u32).max(1);
+
+    (size(width), size(height))
+}
+
+/// Scales image by averaging pixels covered by each target pixel
+fn scale(pixels: &[Rgb], width: u32, height: u32, new_width: u32, new_height: u32) -> Vec<Rgb> {
+    let scale_x = width as f32 / new_width as f32;
+    let scale_y = height as f32 / new_height as f32;
+    let span = |i: u32, scale: f32, size: u32| {
+        let start = ((i as f32 * scale) as u32).min(size - 1);
+        let end = (((i + 1) as f32 * scale).ceil() as u32).clamp(start + 1, size);
+        start..end
+    };
+
+    let mut result = Vec::with_capacity((new_width * new_height) as usize);
+    for y in 0..new_height {
+        for x in 0..new_width {
+            let (xs, ys) = (span(x, scale_x, width), span(y, scale_y, height));
+            let count = (xs.len() * ys.len()) as f32;
+
+            let mut sum = [0.0; 3];
+            for sy in ys {
+                for sx in xs.clone() {
+                    let pixel = pixels[(sx + sy * width) as usize];
+                    sum = [0, 1, 2].map(|i| sum[i] + pixel[i]);
+                }
+            }
+
+            result.push(sum.map(|c| c / count));
+        }
+    }
+
+    result
+}
+
+/// Quantizes pixels to states, diffusing error of each pixel to its neighbours if dithering
+fn convert(pixels: &[Rgb], width: u32, height: u32, options: &ImageOptions) -> Pattern {
+    let multistate = options.colors.len() > 2;
+
+    // Two-state images are quantized by luminance
+    let mut values: Vec<Rgb> = pixels
+        .iter()
+        .map(|&p| match multistate {
+            true => p,
+            false => {
+                let luminance = 0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2];
+                match options.invert {
+                    true => [1.0 - luminance; 3],
+                    false => [luminance; 3],
+                }
+            }
+        })
+        .collect();
+
+    let quantize = |value: Rgb| -> (u32, Rgb) {
+        if !multistate {
+            let alive = value[0] >= options.threshold;
+            return (alive as u32, [if alive { 1.0 } else { 0.0 }; 3]);
+        }
+
+        let distance = |c: &Rgb| (0..3).map(|i| (c[i] - value[i]).powi(2)).sum::<f32>();
+        let (state, color) = options
+            .colors
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .unwrap();
+
+        (state as u32, *color)
+    };
+
+    let mut pattern = Pattern::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let idx = (x + y * width) as usize;
+            let (state, color) = quantize(values[idx]);
+            pattern.set(x, y, state);
+
+            if !options.dither {
+                continue;
+            }
+
+            let error = [0, 1, 2].map(|i| values[idx][i] - color[i]);
+            let neighbours = [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+            for (dx, dy, weight) in neighbours {
+                let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                if nx < 0 || nx >= width as i64 || ny >= height as i64 {
+                    continue;
+                }
+
+                let neighbour = &mut values[(nx + ny * width as i64) as usize];
+                for i in 0..3 {
+                    neighbour[i] += error[i] * weight / 16.0;
+                }
+            }
+        }
+    }
+
+    pattern
+}
@@ -0,0 +1,29 @@
+//! Classic patterns embedded in binary
+
+use super::{rle, Pattern};
+
+/// RLE of built-in patterns
+const PATTERNS: &[&str] = &[
+    include_str!("../../patterns/glider.rle"),
+    include_str!("../../patterns/lwss.rle"),
+    include_str!("../../patterns/mwss.rle"),
+    include_str!("../../patterns/hwss.rle"),
+    include_str!("../../patterns/gosper-gun.rle"),
+    include_str!("../../patterns/pulsar.rle"),
+    include_str!("../../patterns/pentadecathlon.rle"),
+    include_str!("../../patterns/beacon.rle"),
+    include_str!("../../patterns/toad.rle"),
+    include_str!("../../patterns/r-pentomino.rle"),
+    include_str!("../../patterns/acorn.rle"),
+    include_str!("../../patterns/diehard.rle"),
+];
+
+/// Parses all built-in patterns
+pub fn patterns() -> Vec<Pattern> {
+    PATTERNS
+        .iter()
+        .map(|contents| {
+            rle::parse(contents, u32::MAX, u32::MAX).expect("Built-in pattern must be valid")
+        })
+        .collect()
+}
@@ -0,0 +1,216 @@
+//! Life 1.05 pattern format: blocks of plaintext cells with offsets, e.g.
+//! ```text
+//! #Life 1.05
+//! #D Glider
+//! #N
+//! #P -1 -1
+//! .*.
+//! ..*
+//! ***
+//! ```
+
+use anyhow::{anyhow, bail, Context};
+
+use super::{Metadata, Pattern};
+use crate::rule::RuleTable;
+
+/// Header of Life 1.05 file
+pub const HEADER: &str = "#Life 1.05";
+
+/// Max width of block of cells, lines shouldn't be longer than 80 characters
+const BLOCK_WIDTH: u32 = 80;
+
+/// Rule of `#N` line
+const NORMAL_RULE: &str = "B3/S23";
+
+/// Parses Life 1.05 pattern, `#D` lines are read as comments and `#N`, `#R` as rule.
+///
+/// Fails if pattern is larger than `max_width`x`max_height`.
+pub fn parse(contents: &str, max_width: u32, max_height: u32) -> anyhow::Result<Pattern> {
+    let mut metadata = Metadata::default();
+    let mut cells = Vec::new();
+    let mut block = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with(HEADER) {
+            continue;
+        }
+
+        if let Some(line) = line.strip_prefix('#') {
+            let mut chars = line.chars();
+            let kind = chars.next();
+            let text = chars.as_str().trim();
+
+            match kind {
+                Some('D') => metadata.comments.push(text.to_owned()),
+                Some('N') => metadata.rule = Some(NORMAL_RULE.to_owned()),
+                // Rule is in `S/B` form, e.g. `23/3`
+                Some('R') => metadata.rule = Some(text.to_owned()),
+                Some('P') => block = Some((parse_position(text)?, 0)),
+                _ => {}
+            }
+
+            continue;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let ((x, y), row) = block
+            .as_mut()
+            .ok_or_else(|| anyhow!("Cells before `#P` line"))?;
+
+        for (i, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                '*' => {
+                    let cell = x.checked_add(i as i64).zip(y.checked_add(*row));
+                    cells.push(cell.ok_or_else(|| anyhow!("Cell is too far from origin"))?);
+                }
+                _ => bail!("Unexpected `{}` in pattern", c),
+            }
+        }
+
+        *row += 1;
+    }
+
+    let mut pattern = Pattern::from_cells(&cells, max_width, max_height)?;
+    pattern.metadata = metadata;
+
+    Ok(pattern)
+}
+
+/// Parses `x y` position of block
+fn parse_position(text: &str) -> anyhow::Result<(i64, i64)> {
+    let numbers = text
+        .split_whitespace()
+        .map(str::parse::<i64>)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid block position `{}`", text))?;
+
+    match numbers[..] {
+        [x, y] => Ok((x, y)),
+        _ => bail!("Invalid block position `{}`, expected `x y`", text),
+    }
+}
+
+/// Writes pattern centered at origin, wide patterns are split into blocks of 80 columns.
+///
+/// All non-zero states are alive.
+pub fn write(pattern: &Pattern) -> String {
+    let metadata = pattern.metadata();
+    let mut out = format!("{}\n", HEADER);
+
+    if let Some(name) = &metadata.name {
+        out.push_str(&format!("#D {}\n", name));
+    }
+    for comment in &metadata.comments {
+        out.push_str(&format!("#D {}\n", comment));
+    }
+
+    // `#R` has survival conditions first, e.g. `#R 23/36` for `B36/S23`,
+    // unknown rule is omitted, as `#N` would declare pattern as Conway's
+    match metadata
+        .rule
+        .as_deref()
+        .map(|rule| (rule, RuleTable::parse(rule)))
+    {
+        None => {}
+        Some((_, Ok(table))) if table.name() == NORMAL_RULE => out.push_str("#N\n"),
+        Some((_, Ok(table))) => {
+            let (birth, survival) = table.name().split_once('/').unwrap_or_default();
+            out.push_str(&format!(
+                "#R {}/{}\n",
+                survival.trim_start_matches('S'),
+                birth.trim_start_matches('B')
+            ));
+        }
+        // Rules from rule files have no `S/B` form
+        Some((rule, Err(_))) => out.push_str(&format!("#D Rule: {}\n", rule)),
+    }
+
+    let left = -(pattern.width() as i64 / 2);
+    let top = -(pattern.height() as i64 / 2);
+
+    for start in (0..pattern.width()).step_by(BLOCK_WIDTH as usize) {
+        let end = (start + BLOCK_WIDTH).min(pattern.width());
+        let rows: Vec<&[u32]> = (0..pattern.height())
+            .map(|y| &pattern.row(y)[start as usize..end as usize])
+            .collect();
+
+        let alive = |row: &&[u32]| row.iter().any(|&s| s > 0);
+        let (first, last) = match (rows.iter().position(alive), rows.iter().rposition(alive)) {
+            (Some(first), Some(last)) => (first, last),
+            _ => continue,
+        };
+
+        out.push_str(&format!(
+            "#P {} {}\n",
+            left + start as i64,
+            top + first as i64
+        ));
+
+        for row in &rows[first..=last] {
+            // Empty row is written as single dead cell to be visible
+            let end = row.iter().rposition(|&s| s > 0).map_or(1, |p| p + 1);
+            out.extend(row[..end].iter().map(|&s| if s > 0 { '*' } else { '.' }));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#Life 1.05\n#D Glider\n#R 23/36\n#P -1 -1\n.*.\n..*\n***\n";
+
+    #[test]
+    fn writes_rule_survival_first() {
+        let mut pattern = parse(GLIDER, 10, 10).unwrap();
+        assert_eq!(pattern.metadata().rule.as_deref(), Some("23/36"));
+        assert!(write(&pattern).contains("#R 23/36\n"));
+
+        pattern.metadata_mut().rule = Some("B36/S23".into());
+        assert!(write(&pattern).contains("#R 23/36\n"));
+
+        pattern.metadata_mut().rule = Some("B3/S23".into());
+        assert!(write(&pattern).contains("#N\n"));
+    }
+
+    #[test]
+    fn round_trips_cells() {
+        let pattern = parse(GLIDER, 10, 10).unwrap();
+        assert_eq!(parse(&write(&pattern), 10, 10).unwrap(), pattern);
+    }
+
+    #[test]
+    fn rejects_patterns_larger_than_limit() {
+        assert!(parse(GLIDER, 2, 10).is_err());
+
+        let far = "#Life 1.05\n#P -9223372036854775808 0\n*\n#P 9223372036854775807 0\n*\n";
+        assert!(parse(far, u32::MAX, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn rejects_cells_overflowing_offset() {
+        let right = "#Life 1.05\n#P 9223372036854775807 0\n.*\n";
+        assert!(parse(right, u32::MAX, u32::MAX).is_err());
+
+        let down = "#Life 1.05\n#P 0 9223372036854775807\n*\n*\n";
+        assert!(parse(down, u32::MAX, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn omits_unknown_rule() {
+        let mut pattern = parse(GLIDER, 10, 10).unwrap();
+        pattern.metadata_mut().rule = None;
+
+        let written = write(&pattern);
+        assert!(!written.contains("#N") && !written.contains("#R"));
+        assert_eq!(parse(&written, 10, 10).unwrap().metadata().rule, None);
+    }
+}
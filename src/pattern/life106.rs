@@ -0,0 +1,74 @@
+//! Life 1.06 pattern format: coordinates of alive cells, e.g.
+//! ```text
+//! #Life 1.06
+//! 0 -1
+//! 1 0
+//! -1 1
+//! 0 1
+//! 1 1
+//! ```
+
+use anyhow::{bail, Context};
+
+use super::Pattern;
+
+/// Header of Life 1.06 file
+pub const HEADER: &str = "#Life 1.06";
+
+/// Parses Life 1.06 pattern, the format has no metadata.
+///
+/// Fails if pattern is larger than `max_width`x`max_height`.
+pub fn parse(contents: &str, max_width: u32, max_height: u32) -> anyhow::Result<Pattern> {
+    let mut cells = Vec::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let numbers = line
+            .split_whitespace()
+            .map(str::parse::<i64>)
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid cell `{}`", line))?;
+
+        match numbers[..] {
+            [x, y] => cells.push((x, y)),
+            _ => bail!("Invalid cell `{}`, expected `x y`", line),
+        }
+    }
+
+    Pattern::from_cells(&cells, max_width, max_height)
+}
+
+/// Writes alive cells relative to top left corner of pattern row by row.
+///
+/// All non-zero states are alive, metadata is lost.
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = format!("{}\n", HEADER);
+
+    for y in 0..pattern.height() {
+        for (x, &state) in pattern.row(y).iter().enumerate() {
+            if state > 0 {
+                out.push_str(&format!("{} {}\n", x, y));
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_patterns_larger_than_limit() {
+        let glider = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        assert_eq!(parse(glider, 3, 3).unwrap().width(), 3);
+        assert!(parse(glider, 3, 2).is_err());
+
+        let wide = "#Life 1.06\n0 0\n4294967296 0\n";
+        assert!(parse(wide, u32::MAX, u32::MAX).is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! Golly's Macrocell pattern format: quadtree of nodes referencing previous ones, e.g.
+//! ```text
+//! [M2] (golly 2.0)
+//! #R B3/S23
+//! .*$..*$***$
+//! 4 1 0 0 0
+//! ```
+//!
+//! Two-state patterns have 8x8 leaves of `.`, `*` with rows ended by `$`.
+//! Multi-state patterns have `1 nw ne sw se` leaves of states.
+//! Other nodes are `level nw ne sw se`, children are numbers of previous node lines, `0` is empty.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
+
+use super::{Metadata, Pattern};
+use crate::rule::MAX_STATES;
+
+/// Header of Macrocell file
+pub const HEADER: &str = "[M2]";
+
+/// Level of leaves of two-state patterns, they are 8x8
+const LEAF_LEVEL: u32 = 3;
+
+/// Max level of node, so coordinates fit into `i64`
+const MAX_LEVEL: u32 = 62;
+
+enum Node {
+    /// Square of states, rows from the top
+    Leaf(Vec<u32>),
+    /// Children `nw ne sw se`, `0` is empty node
+    Branch([usize; 4]),
+}
+
+/// Bounding box of alive cells `(left, top, right, bottom)`, inclusive
+type Bounds = (i64, i64, i64, i64);
+
+/// Pattern as quadtree with shared nodes.
+///
+/// Stores patterns of any size sparsely, parts of them are decoded as [`Pattern`].
+pub struct Macrocell {
+    /// Nodes with their levels, node `n` is at `n - 1`
+    nodes: Vec<(u32, Node)>,
+    metadata: Metadata,
+}
+
+impl Macrocell {
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut lines = contents.lines().map(str::trim);
+        match lines.next() {
+            Some(header) if header.starts_with(HEADER) => {}
+            _ => bail!("Missing `{}` header", HEADER),
+        }
+
+        let mut metadata = Metadata::default();
+        let mut nodes = Vec::new();
+
+        for line in lines.filter(|l| !l.is_empty()) {
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut chars = comment.chars();
+                let kind = chars.next();
+                let text = chars.as_str().trim().to_owned();
+
+                match kind {
+                    Some('R') => metadata.rule = Some(text),
+                    Some('N') => metadata.name = Some(text),
+                    Some('O') => metadata.author = Some(text),
+                    Some('C') => metadata.comments.push(text),
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            let node = if line.starts_with(|c: char| c.is_ascii_digit()) {
+                parse_node(line, nodes.len())
+            } else {
+                parse_leaf(line)
+            };
+
+            nodes.push(node.with_context(|| format!("Invalid node {}", nodes.len() + 1))?);
+        }
+
+        if nodes.is_empty() {
+            bail!("Pattern has no nodes");
+        }
+
+        for (level, node) in &nodes {
+            if let Node::Branch(children) = node {
+                for &child in children.iter().filter(|&&c| c > 0) {
+                    if nodes[child - 1].0 + 1 != *level {
+                        bail!(
+                            "Child {} has wrong level for node of level {}",
+                            child,
+                            level
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(Self { nodes, metadata })
+    }
+
+    /// Builds quadtree of pattern
+    pub fn from_pattern(pattern: &Pattern) -> Self {
+        let multistate = (0..pattern.height()).any(|y| pattern.row(y).iter().any(|&s| s > 1));
+        let leaf_level = if multistate { 1 } else { LEAF_LEVEL };
+
+        let side = pattern.width().max(pattern.height()).max(1);
+        let level = side.next_power_of_two().trailing_zeros().max(leaf_level);
+
+        let mut builder = Builder {
+            pattern,
+            leaf_level,
+            nodes: Vec::new(),
+            known: HashMap::new(),
+        };
+
+        let root = builder.build(level, 0, 0);
+        let mut nodes = builder.nodes;
+
+        // Root must be the last node even if pattern is empty, empty leaf would be written
+        // as blank line, so root is node of empty children
+        if root == 0 {
+            nodes.push((level.max(leaf_level + 1), Node::Branch([0; 4])));
+        }
+
+        Self {
+            nodes,
+            metadata: pattern.metadata().clone(),
+        }
+    }
+
+    fn root(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Bounding box of alive cells relative to top left corner of the root
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut known = vec![None; self.nodes.len() + 1];
+        self.node_bounds(self.root(), &mut known)
+    }
+
+    /// Bounding box of node, `known` holds bounds of visited nodes
+    fn node_bounds(&self, node: usize, known: &mut [Option<Option<Bounds>>]) -> Option<Bounds> {
+        if node == 0 {
+            return None;
+        }
+
+        if let Some(bounds) = known[node] {
+            return bounds;
+        }
+
+        let (level, ref kind) = self.nodes[node - 1];
+        let bounds = match kind {
+            Node::Leaf(cells) => {
+                let side = 1i64 << level;
+                let alive = (0..side * side).filter(|&i| cells[i as usize] > 0);
+                alive.fold(None, |bounds, i| {
+                    union(bounds, Some((i % side, i / side, i % side, i / side)))
+                })
+            }
+            Node::Branch(children) => {
+                let half = 1i64 << (level - 1);
+                let mut bounds = None;
+                for (i, &child) in children.iter().enumerate() {
+                    let (dx, dy) = ((i as i64 % 2) * half, (i as i64 / 2) * half);
+                    let child = self
+                        .node_bounds(child, known)
+                        .map(|(l, t, r, b)| (l + dx, t + dy, r + dx, b + dy));
+                    bounds = union(bounds, child);
+                }
+                bounds
+            }
+        };
+
+        known[node] = Some(bounds);
+        bounds
+    }
+
+    /// Decodes rectangle of cells relative to top left corner of the root
+    pub fn region(&self, x: i64, y: i64, width: u32, height: u32) -> Pattern {
+        let mut pattern = Pattern::new(width, height);
+        pattern.metadata = self.metadata.clone();
+
+        let window = (x, y, x + width as i64 - 1, y + height as i64 - 1);
+        self.decode(self.root(), 0, 0, window, &mut pattern);
+
+        pattern
+    }
+
+    fn decode(&self, node: usize, x: i64, y: i64, window: Bounds, pattern: &mut Pattern) {
+        if node == 0 {
+            return;
+        }
+
+        let (level, ref kind) = self.nodes[node - 1];
+        let side = 1i64 << level;
+        let (left, top, right, bottom) = window;
+        if x > right || y > bottom || x + side <= left || y + side <= top {
+            return;
+        }
+
+        match kind {
+            Node::Leaf(cells) => {
+                for (i, &state) in cells.iter().enumerate() {
+                    let (cx, cy) = (x + i as i64 % side, y + i as i64 / side);
+                    if state > 0 && (left..=right).contains(&cx) && (top..=bottom).contains(&cy) {
+                        pattern.set((cx - left) as u32, (cy - top) as u32, state);
+                    }
+                }
+            }
+            Node::Branch(children) => {
+                let half = side / 2;
+                for (i, &child) in children.iter().enumerate() {
+                    let (dx, dy) = ((i as i64 % 2) * half, (i as i64 / 2) * half);
+                    self.decode(child, x + dx, y + dy, window, pattern);
+                }
+            }
+        }
+    }
+
+    /// Size of bounding box of alive cells, `(0, 0)` if there are none
+    pub fn size(&self) -> (i64, i64) {
+        match self.bounds() {
+            Some((left, top, right, bottom)) => (right - left + 1, bottom - top + 1),
+            None => (0, 0),
+        }
+    }
+
+    /// Decodes alive cells, pattern must be small enough to be stored densely
+    pub fn to_pattern(&self) -> Pattern {
+        let (width, height) = self.size();
+        let (left, top, _, _) = self.bounds().unwrap_or_default();
+
+        self.region(left, top, width as u32, height as u32)
+    }
+
+    /// Decodes `width`x`height` rectangle of cells centred at centre of alive cells
+    pub fn centre(&self, width: u32, height: u32) -> (Pattern, i64, i64) {
+        let (left, top, right, bottom) = self.bounds().unwrap_or_default();
+        let x = (left + right + 1) / 2 - width as i64 / 2;
+        let y = (top + bottom + 1) / 2 - height as i64 / 2;
+
+        (self.region(x, y, width, height), x, y)
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn write(&self) -> String {
+        let mut out = format!("{} (life-wgpu-rs)\n", HEADER);
+
+        let metadata = &self.metadata;
+        if let Some(rule) = &metadata.rule {
+            out.push_str(&format!("#R {}\n", rule));
+        }
+        if let Some(name) = &metadata.name {
+            out.push_str(&format!("#N {}\n", name));
+        }
+        if let Some(author) = &metadata.author {
+            out.push_str(&format!("#O {}\n", author));
+        }
+        for comment in &metadata.comments {
+            out.push_str(&format!("#C {}\n", comment));
+        }
+
+        for (level, node) in &self.nodes {
+            match node {
+                Node::Leaf(cells) if *level == 1 => {
+                    out.push_str(&format!(
+                        "1 {} {} {} {}\n",
+                        cells[0], cells[1], cells[2], cells[3]
+                    ));
+                }
+                Node::Leaf(cells) => {
+                    let last = cells.iter().rposition(|&s| s > 0).map_or(0, |p| p / 8 + 1);
+                    for row in cells.chunks(8).take(last) {
+                        let end = row.iter().rposition(|&s| s > 0).map_or(0, |p| p + 1);
+                        out.extend(row[..end].iter().map(|&s| if s > 0 { '*' } else { '.' }));
+                        out.push('$');
+                    }
+                    out.push('\n');
+                }
+                Node::Branch([nw, ne, sw, se]) => {
+                    out.push_str(&format!("{} {} {} {} {}\n", level, nw, ne, sw, se));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn union(a: Option<Bounds>, b: Option<Bounds>) -> Option<Bounds> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some((a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Parses `level nw ne sw se` node, states of `1 a b c d` leaf
+fn parse_node(line: &str, count: usize) -> anyhow::Result<(u32, Node)> {
+    let numbers = line
+        .split_whitespace()
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid node `{}`", line))?;
+
+    let (level, children) = match numbers[..] {
+        [level, nw, ne, sw, se] => (level as u32, [nw, ne, sw, se]),
+        _ => bail!("Invalid node `{}`, expected `level nw ne sw se`", line),
+    };
+
+    match level {
+        0 => bail!("Node can't have level 0"),
+        1 => {
+            if let Some(state) = children.iter().find(|&&s| s >= MAX_STATES as usize) {
+                bail!("State {} is out of range", state);
+            }
+
+            Ok((1, Node::Leaf(children.iter().map(|&s| s as u32).collect())))
+        }
+        _ if level > MAX_LEVEL => bail!("Node level {} is too large", level),
+        _ => {
+            if let Some(child) = children.iter().find(|&&c| c > count) {
+                bail!("Node {} isn't defined yet", child);
+            }
+
+            Ok((level, Node::Branch(children)))
+        }
+    }
+}
+
+/// Parses 8x8 leaf of two-state pattern, e.g. `.*$..*$***$`
+fn parse_leaf(line: &str) -> anyhow::Result<(u32, Node)> {
+    let mut cells = vec![0; 64];
+    let (mut x, mut y) = (0, 0);
+
+    for c in line.chars() {
+        match c {
+            '.' => x += 1,
+            '*' => {
+                if x >= 8 || y >= 8 {
+                    bail!("Leaf `{}` is larger than 8x8", line);
+                }
+                cells[x + y * 8] = 1;
+                x += 1;
+            }
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            _ => return Err(anyhow!("Unexpected `{}` in leaf", c)),
+        }
+    }
+
+    Ok((LEAF_LEVEL, Node::Leaf(cells)))
+}
+
+/// Builds quadtree from pattern sharing equal nodes
+struct Builder<'p> {
+    pattern: &'p Pattern,
+    leaf_level: u32,
+    nodes: Vec<(u32, Node)>,
+    known: HashMap<(u32, Vec<u32>), usize>,
+}
+
+impl Builder<'_> {
+    /// Returns number of node with top left corner at cell, `0` if it's empty
+    fn build(&mut self, level: u32, x: u32, y: u32) -> usize {
+        let side = 1u32 << level;
+        if x >= self.pattern.width() || y >= self.pattern.height() {
+            return 0;
+        }
+
+        let (key, node) = if level == self.leaf_level {
+            let cells: Vec<u32> = (0..side * side)
+                .map(|i| {
+                    let (cx, cy) = (x + i % side, y + i / side);
+                    let inside = cx < self.pattern.width() && cy < self.pattern.height();
+                    if inside {
+                        self.pattern.get(cx, cy)
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+
+            if cells.iter().all(|&s| s == 0) {
+                return 0;
+            }
+
+            (cells.clone(), Node::Leaf(cells))
+        } else {
+            let half = side / 2;
+            let children = [
+                self.build(level - 1, x, y),
+                self.build(level - 1, x + half, y),
+                self.build(level - 1, x, y + half),
+                self.build(level - 1, x + half, y + half),
+            ];
+
+            if children == [0; 4] {
+                return 0;
+            }
+
+            let key = children.iter().map(|&c| c as u32).collect();
+            (key, Node::Branch(children))
+        };
+
+        if let Some(&known) = self.known.get(&(level, key.clone())) {
+            return known;
+        }
+
+        self.nodes.push((level, node));
+        self.known.insert((level, key), self.nodes.len());
+
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Glider in north east leaf of node of level 4
+    const GLIDER: &str = "[M2] (golly 2.0)\n#R B3/S23\n.*$..*$***$\n4 0 1 0 0\n";
+
+    #[test]
+    fn decodes_bounding_box() {
+        let macrocell = Macrocell::parse(GLIDER).unwrap();
+        assert_eq!(macrocell.bounds(), Some((8, 0, 10, 2)));
+        assert_eq!(macrocell.size(), (3, 3));
+
+        let pattern = macrocell.to_pattern();
+        let rows: Vec<&[u32]> = (0..3).map(|y| pattern.row(y)).collect();
+        assert_eq!(rows, [[0, 1, 0], [0, 0, 1], [1, 1, 1]]);
+        assert_eq!(pattern.metadata().rule.as_deref(), Some("B3/S23"));
+    }
+
+    #[test]
+    fn decodes_window_around_centre() {
+        let macrocell = Macrocell::parse(GLIDER).unwrap();
+        let (window, left, top) = macrocell.centre(5, 5);
+        assert_eq!((left, top), (7, -1));
+        assert_eq!(window.row(1), [0, 0, 1, 0, 0]);
+        assert_eq!(window.row(2), [0, 0, 0, 1, 0]);
+        assert_eq!(window.row(3), [0, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn round_trips_through_quadtree() {
+        let mut pattern = Pattern::new(20, 13);
+        for (x, y, state) in [(0, 0, 1), (19, 12, 1), (7, 9, 1)] {
+            pattern.set(x, y, state);
+        }
+        let written = Macrocell::from_pattern(&pattern).write();
+        assert_eq!(Macrocell::parse(&written).unwrap().to_pattern(), pattern);
+
+        pattern.set(3, 3, 5);
+        let written = Macrocell::from_pattern(&pattern).write();
+        assert_eq!(Macrocell::parse(&written).unwrap().to_pattern(), pattern);
+    }
+
+    #[test]
+    fn round_trips_empty_pattern() {
+        for empty in [Pattern::new(20, 13), Pattern::new(0, 0)] {
+            let written = Macrocell::from_pattern(&empty).write();
+            assert!(written.ends_with(" 0 0 0 0\n"), "{}", written);
+            assert!(written.lines().all(|line| !line.is_empty()));
+
+            let macrocell = Macrocell::parse(&written).unwrap();
+            assert_eq!(macrocell.bounds(), None);
+            assert_eq!(macrocell.to_pattern(), Pattern::new(0, 0));
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context};
+
+use macrocell::Macrocell;
+
+pub mod apgcode;
+pub mod image;
+pub mod library;
+pub mod life105;
+pub mod life106;
+pub mod macrocell;
+pub mod plaintext;
+pub mod rle;
+
+/// Pattern file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Rle,
+    Plaintext,
+    Life105,
+    Life106,
+    Macrocell,
+}
+
+impl Format {
+    /// Format by name: `rle`, `cells`, `life105`, `life106` or `mc`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rle" => Some(Self::Rle),
+            "cells" => Some(Self::Plaintext),
+            "life105" => Some(Self::Life105),
+            "life106" => Some(Self::Life106),
+            "mc" => Some(Self::Macrocell),
+            _ => None,
+        }
+    }
+
+    /// Detects format by extension of file, or by its contents if extension is ambiguous
+    pub fn detect(path: &Path, contents: &str) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("rle") => Self::Rle,
+            Some(e) if e.eq_ignore_ascii_case("cells") => Self::Plaintext,
+            Some(e) if e.eq_ignore_ascii_case("mc") => Self::Macrocell,
+            _ => Self::sniff(contents),
+        }
+    }
+
+    /// Detects format by first lines of file
+    pub fn sniff(contents: &str) -> Self {
+        for line in contents.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if line.starts_with(life105::HEADER) || line.starts_with("#P") {
+                return Self::Life105;
+            } else if line.starts_with(life106::HEADER) {
+                return Self::Life106;
+            } else if line.starts_with(macrocell::HEADER) {
+                return Self::Macrocell;
+            } else if line.starts_with('!') || line.chars().all(|c| matches!(c, '.' | 'O')) {
+                return Self::Plaintext;
+            } else if !line.starts_with('#') {
+                break;
+            }
+        }
+
+        Self::Rle
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Rle => "rle",
+            Format::Plaintext => "cells",
+            Format::Life105 | Format::Life106 => "lif",
+            Format::Macrocell => "mc",
+        }
+    }
+
+    pub fn write(&self, pattern: &Pattern) -> String {
+        match self {
+            Format::Rle => rle::write(pattern),
+            Format::Plaintext => plaintext::write(pattern),
+            Format::Life105 => life105::write(pattern),
+            Format::Life106 => life106::write(pattern),
+            Format::Macrocell => Macrocell::from_pattern(pattern).write(),
+        }
+    }
+}
+
+/// Metadata of pattern from comments and header of pattern file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    /// Rule named by pattern file
+    pub rule: Option<String>,
+}
+
+/// Rectangular block of cells.
+///
+/// Rows are stored from the top one, as in pattern files, while on the field north is `+y`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    width: u32,
+    height: u32,
+    cells: Vec<u32>,
+
+    metadata: Metadata,
+}
+
+impl Pattern {
+    /// Creates pattern of dead cells.
+    ///
+    /// Panics if pattern has more than `u32::MAX` cells, sizes from files go through
+    /// [`Pattern::try_new`].
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::try_new(width, height).expect("Pattern is too large")
+    }
+
+    /// Creates pattern of dead cells, fails if cells can't be indexed with `u32`
+    pub fn try_new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let count = width
+            .checked_mul(height)
+            .ok_or_else(|| anyhow!("Pattern {}x{} is too large", width, height))?;
+
+        Ok(Self {
+            width,
+            height,
+            cells: vec![0; count as usize],
+            metadata: Metadata::default(),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> u32 {
+        self.cells[(x + y * self.width) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, state: u32) {
+        self.cells[(x + y * self.width) as usize] = state;
+    }
+
+    /// Creates pattern of alive cells at coordinates, `y` goes down.
+    ///
+    /// Fails if bounding box of cells is larger than `max_width`x`max_height`.
+    fn from_cells(cells: &[(i64, i64)], max_width: u32, max_height: u32) -> anyhow::Result<Self> {
+        let min_x = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let min_y = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        let max_x = cells.iter().map(|c| c.0).max().unwrap_or(-1);
+        let max_y = cells.iter().map(|c| c.1).max().unwrap_or(-1);
+
+        // Coordinates span at most the whole range of i64
+        let width = (max_x as i128 - min_x as i128 + 1) as u128;
+        let height = (max_y as i128 - min_y as i128 + 1) as u128;
+        if width > max_width as u128 || height > max_height as u128 {
+            bail!(
+                "Pattern {}x{} doesn't fit into field {}x{}",
+                width,
+                height,
+                max_width,
+                max_height
+            );
+        }
+
+        let mut pattern = Self::try_new(width as u32, height as u32)?;
+        for &(x, y) in cells {
+            pattern.set((x - min_x) as u32, (y - min_y) as u32, 1);
+        }
+
+        Ok(pattern)
+    }
+
+    /// Row of cells, `0` is the top one
+    pub fn row(&self, y: u32) -> &[u32] {
+        let start = (y * self.width) as usize;
+        &self.cells[start..start + self.width as usize]
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Pattern rotated by 90 degrees clockwise
+    pub fn rotated(&self) -> Pattern {
+        let mut pattern = Pattern::new(self.height, self.width);
+        pattern.metadata = self.metadata.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                pattern.set(self.height - 1 - y, x, self.get(x, y));
+            }
+        }
+
+        pattern
+    }
+
+    /// Pattern mirrored from left to right
+    pub fn flipped(&self) -> Pattern {
+        let mut pattern = Pattern::new(self.width, self.height);
+        pattern.metadata = self.metadata.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                pattern.set(self.width - 1 - x, y, self.get(x, y));
+            }
+        }
+
+        pattern
+    }
+
+    /// Bounding box of alive cells as `left`, `top`, `width`, `height`, if there are any
+    pub fn bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+        let alive = |x, y| self.get(x, y) > 0;
+        let rows: Vec<u32> = (0..self.height)
+            .filter(|&y| (0..self.width).any(|x| alive(x, y)))
+            .collect();
+        let columns: Vec<u32> = (0..self.width)
+            .filter(|&x| (0..self.height).any(|y| alive(x, y)))
+            .collect();
+
+        let (&top, &left) = (rows.first()?, columns.first()?);
+        let width = columns[columns.len() - 1] - left + 1;
+        let height = rows[rows.len() - 1] - top + 1;
+
+        Some((left, top, width, height))
+    }
+
+    /// Pattern cropped to bounding box of alive cells, empty if there are none
+    pub fn cropped(&self) -> Pattern {
+        let (left, top, width, height) = match self.bounding_box() {
+            Some(bounds) => bounds,
+            None => {
+                let mut empty = Pattern::new(0, 0);
+                empty.metadata = self.metadata.clone();
+                return empty;
+            }
+        };
+
+        let mut pattern = Pattern::new(width, height);
+        pattern.metadata = self.metadata.clone();
+        for y in 0..height {
+            for x in 0..width {
+                pattern.set(x, y, self.get(left + x, top + y));
+            }
+        }
+
+        pattern
+    }
+}
+
+/// Saves pattern to file in format
+pub fn save(pattern: &Pattern, path: &Path, format: Format) -> anyhow::Result<()> {
+    std::fs::write(path, format.write(pattern))
+        .with_context(|| format!("Cannot write pattern file {}", path.display()))
+}
+
+/// Pattern read from file
+pub enum Loaded {
+    Pattern(Pattern),
+    /// Macrocell pattern larger than the field, kept whole as quadtree
+    Sparse(Macrocell),
+}
+
+impl Loaded {
+    pub fn metadata(&self) -> &Metadata {
+        match self {
+            Loaded::Pattern(pattern) => pattern.metadata(),
+            Loaded::Sparse(macrocell) => macrocell.metadata(),
+        }
+    }
+}
+
+/// Loads pattern from file, format is detected automatically.
+///
+/// RLE, plaintext, Life 1.05 and Life 1.06 patterns larger than `max_width`x`max_height` are
+/// rejected, Macrocell ones are kept sparse.
+pub fn load(path: &Path, max_width: u32, max_height: u32) -> anyhow::Result<Loaded> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read pattern file {}", path.display()))?;
+
+    let pattern = match Format::detect(path, &contents) {
+        Format::Rle => rle::parse(&contents, max_width, max_height).map(Loaded::Pattern),
+        Format::Plaintext => {
+            plaintext::parse(&contents, max_width, max_height).map(Loaded::Pattern)
+        }
+        Format::Life105 => life105::parse(&contents, max_width, max_height).map(Loaded::Pattern),
+        Format::Life106 => life106::parse(&contents, max_width, max_height).map(Loaded::Pattern),
+        Format::Macrocell => Macrocell::parse(&contents).map(|macrocell| {
+            let (width, height) = macrocell.size();
+            if width > max_width as i64 || height > max_height as i64 {
+                Loaded::Sparse(macrocell)
+            } else {
+                Loaded::Pattern(macrocell.to_pattern())
+            }
+        }),
+    };
+
+    pattern.with_context(|| format!("Invalid pattern file {}", path.display()))
+}
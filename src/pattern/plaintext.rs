@@ -0,0 +1,134 @@
+//! Plaintext `.cells` pattern format, e.g.
+//! ```text
+//! !Name: Glider
+//! .O.
+//! ..O
+//! OOO
+//! ```
+
+use anyhow::bail;
+
+use super::{Metadata, Pattern};
+
+/// Parses plaintext pattern, `!Name:` and `!Author:` comments are read as metadata.
+///
+/// Fails if pattern is larger than `max_width`x`max_height`, rows are checked as they are read.
+pub fn parse(contents: &str, max_width: u32, max_height: u32) -> anyhow::Result<Pattern> {
+    let mut metadata = Metadata::default();
+    let mut rows = Vec::new();
+
+    for line in contents.lines().map(str::trim_end) {
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("Name:") {
+                metadata.name = Some(name.trim().to_owned());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                metadata.author = Some(author.trim().to_owned());
+            } else {
+                metadata.comments.push(comment.to_owned());
+            }
+
+            continue;
+        }
+
+        let chars = line.chars().count();
+        if chars > max_width as usize {
+            bail!(
+                "Row of {} cells doesn't fit into field {}x{}",
+                chars,
+                max_width,
+                max_height
+            );
+        }
+
+        let row = line
+            .chars()
+            .map(|c| match c {
+                '.' => Ok(0),
+                'O' | '*' => Ok(1),
+                _ => bail!("Unexpected `{}` in pattern", c),
+            })
+            .collect::<anyhow::Result<Vec<u32>>>()?;
+
+        rows.push(row);
+
+        // Empty lines at the end aren't rows, so only rows with cells are checked
+        if rows.len() > max_height as usize && rows.last().is_some_and(|r| !r.is_empty()) {
+            bail!(
+                "Pattern doesn't fit into field {}x{}",
+                max_width,
+                max_height
+            );
+        }
+    }
+
+    // Empty lines at the end aren't rows
+    while rows.last().is_some_and(Vec::is_empty) {
+        rows.pop();
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut pattern = Pattern::try_new(width as u32, rows.len() as u32)?;
+    pattern.metadata = metadata;
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &state) in row.iter().enumerate() {
+            pattern.set(x as u32, y as u32, state);
+        }
+    }
+
+    Ok(pattern)
+}
+
+/// Writes pattern, dead cells at ends of rows are omitted. All non-zero states are alive.
+pub fn write(pattern: &Pattern) -> String {
+    let metadata = pattern.metadata();
+    let mut out = String::new();
+
+    if let Some(name) = &metadata.name {
+        out.push_str(&format!("!Name: {}\n", name));
+    }
+    if let Some(author) = &metadata.author {
+        out.push_str(&format!("!Author: {}\n", author));
+    }
+    for comment in &metadata.comments {
+        out.push_str(&format!("!{}\n", comment));
+    }
+
+    for y in 0..pattern.height() {
+        let row = pattern.row(y);
+        let end = row.iter().rposition(|&s| s > 0).map_or(0, |p| p + 1);
+
+        // Empty row is written as single dead cell to be visible
+        let row = &row[..end.max(1).min(row.len())];
+        out.extend(row.iter().map(|&s| if s > 0 { 'O' } else { '.' }));
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "!Name: Glider\n.O\n..O\nOOO\n";
+
+    #[test]
+    fn round_trips_cells() {
+        let pattern = parse(GLIDER, 3, 3).unwrap();
+        assert_eq!(pattern.metadata().name.as_deref(), Some("Glider"));
+        assert_eq!(write(&pattern), GLIDER);
+    }
+
+    #[test]
+    fn rejects_patterns_larger_than_limit() {
+        assert!(parse(GLIDER, 2, 3).is_err());
+        assert!(parse(GLIDER, 3, 2).is_err());
+        assert!(parse(&".".repeat(100_000), 1024, 1024).is_err());
+        assert!(parse(&"O\n".repeat(100_000), 1024, 1024).is_err());
+
+        // Empty lines at the end don't count
+        assert!(parse(&format!("{}\n\n\n", GLIDER), 3, 3).is_ok());
+    }
+}
@@ -0,0 +1,413 @@
+//! Run Length Encoded pattern format, e.g.
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Context};
+
+use super::{Metadata, Pattern};
+use crate::rule::MAX_STATES;
+
+/// Max length of line of written pattern
+const LINE_LENGTH: usize = 70;
+
+/// Parses RLE pattern with its header and `#N`, `#O`, `#C` comments.
+///
+/// Size in header is checked against `max_width`x`max_height` before cells are allocated.
+pub fn parse(contents: &str, max_width: u32, max_height: u32) -> anyhow::Result<Pattern> {
+    let mut metadata = Metadata::default();
+    let mut lines = contents.lines().map(str::trim);
+
+    let header = loop {
+        let line = lines
+            .next()
+            .ok_or_else(|| anyhow!("Missing `x = .., y = ..` header"))?;
+
+        match line.strip_prefix('#') {
+            Some(comment) => parse_comment(comment, &mut metadata),
+            None if line.is_empty() => {}
+            None => break line,
+        }
+    };
+
+    let (width, height, rule) = parse_header(header)?;
+    if rule.is_some() {
+        metadata.rule = rule;
+    }
+
+    if width > max_width || height > max_height {
+        bail!(
+            "Pattern {}x{} doesn't fit into field {}x{}",
+            width,
+            height,
+            max_width,
+            max_height
+        );
+    }
+
+    let mut pattern = Pattern::try_new(width, height)?;
+    pattern.metadata = metadata;
+
+    let mut body = lines
+        .flat_map(str::chars)
+        .filter(|c| !c.is_whitespace())
+        .peekable();
+    let (mut x, mut y) = (0u32, 0u32);
+    let mut count = None;
+
+    while let Some(c) = body.next() {
+        if let Some(digit) = c.to_digit(10) {
+            count = Some(
+                count
+                    .unwrap_or(0u32)
+                    .saturating_mul(10)
+                    .saturating_add(digit),
+            );
+            continue;
+        }
+
+        let run = count.take().unwrap_or(1);
+        let state = match c {
+            '!' => break,
+            '$' => {
+                x = 0;
+                y = y.saturating_add(run);
+                continue;
+            }
+            'b' | '.' => 0,
+            'A'..='X' => c as u32 - 'A' as u32 + 1,
+            'p'..='y' if body.peek().is_some_and(|l| ('A'..='X').contains(l)) => {
+                let letter = body.next().unwrap();
+                (c as u32 - 'p' as u32 + 1) * 24 + letter as u32 - 'A' as u32 + 1
+            }
+            // Two state patterns may use any other letter for alive cells
+            'a'..='z' => 1,
+            _ => bail!("Unexpected `{}` in pattern", c),
+        };
+
+        if state >= MAX_STATES {
+            bail!("State {} is out of range", state);
+        }
+
+        if state > 0 {
+            if x.saturating_add(run) > width || y >= height {
+                bail!("Pattern doesn't fit into {}x{} from header", width, height);
+            }
+
+            for i in x..x + run {
+                pattern.set(i, y, state);
+            }
+        }
+
+        x = x.saturating_add(run);
+    }
+
+    Ok(pattern)
+}
+
+fn parse_comment(comment: &str, metadata: &mut Metadata) {
+    let mut chars = comment.chars();
+    let kind = chars.next();
+    let text = chars.as_str().trim().to_owned();
+
+    match kind {
+        Some('N') => metadata.name = Some(text),
+        Some('O') => metadata.author = Some(text),
+        Some('C' | 'c') => metadata.comments.push(text),
+        // Old format of rule, e.g. `#r 23/3`
+        Some('r') => metadata.rule = Some(text),
+        _ => {}
+    }
+}
+
+/// Parses `x = 3, y = 3, rule = B3/S23` header
+fn parse_header(header: &str) -> anyhow::Result<(u32, u32, Option<String>)> {
+    let (mut width, mut height, mut rule) = (None, None, None);
+    let mut rest = header;
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let key = key.trim();
+
+        // Rule may have commas, e.g. bounded grid `B3/S23:T20,20`
+        if key == "rule" {
+            rule = Some(value.trim().to_owned());
+            break;
+        }
+
+        let (value, tail) = value.split_once(',').unwrap_or((value, ""));
+        let parse = || {
+            value
+                .trim()
+                .parse::<u32>()
+                .with_context(|| format!("Invalid `{}` in header `{}`", key, header))
+        };
+
+        match key {
+            "x" => width = Some(parse()?),
+            "y" => height = Some(parse()?),
+            _ => {}
+        }
+
+        rest = tail;
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => bail!("Invalid header `{}`, expected `x = .., y = ..`", header),
+    }
+}
+
+/// Writes pattern in canonical form: runs of dead cells at ends of rows are omitted
+/// and lines are wrapped at 70 characters without splitting runs.
+///
+/// Multi-state letters are used if pattern has states other than 0 and 1.
+pub fn write(pattern: &Pattern) -> String {
+    let metadata = pattern.metadata();
+    let mut out = String::new();
+
+    // Writing to String can't fail
+    if let Some(name) = &metadata.name {
+        writeln!(out, "#N {}", name).unwrap();
+    }
+    if let Some(author) = &metadata.author {
+        writeln!(out, "#O {}", author).unwrap();
+    }
+    for comment in &metadata.comments {
+        writeln!(out, "#C {}", comment).unwrap();
+    }
+
+    write!(out, "x = {}, y = {}", pattern.width(), pattern.height()).unwrap();
+    if let Some(rule) = &metadata.rule {
+        write!(out, ", rule = {}", rule).unwrap();
+    }
+    out.push('\n');
+
+    let multistate = (0..pattern.height()).any(|y| pattern.row(y).iter().any(|&s| s > 1));
+    let mut body = Body { out, line: 0 };
+    let mut rows_ended = 0;
+
+    for y in 0..pattern.height() {
+        let row = pattern.row(y);
+        let end = row.iter().rposition(|&s| s > 0).map_or(0, |p| p + 1);
+
+        if end > 0 {
+            if rows_ended > 0 {
+                body.push(rows_ended, "$");
+            }
+            rows_ended = 0;
+
+            for run in row[..end].chunk_by(|a, b| a == b) {
+                body.push(run.len() as u32, &symbol(run[0], multistate));
+            }
+        }
+
+        rows_ended += 1;
+    }
+
+    body.push(1, "!");
+    body.out.push('\n');
+
+    body.out
+}
+
+/// Symbol of state in pattern body
+fn symbol(state: u32, multistate: bool) -> String {
+    let letter = |n: u32| char::from(b'A' + n as u8);
+
+    match (state, multistate) {
+        (0, false) => "b".into(),
+        (_, false) => "o".into(),
+        (0, true) => ".".into(),
+        (1..=24, true) => letter(state - 1).into(),
+        _ => {
+            let prefix = char::from(b'p' + ((state - 25) / 24) as u8);
+            format!("{}{}", prefix, letter((state - 25) % 24))
+        }
+    }
+}
+
+/// Body of pattern being written, wraps lines
+struct Body {
+    out: String,
+    line: usize,
+}
+
+impl Body {
+    fn push(&mut self, count: u32, symbol: &str) {
+        let run = match count {
+            1 => symbol.to_owned(),
+            _ => format!("{}{}", count, symbol),
+        };
+
+        if self.line + run.len() > LINE_LENGTH {
+            self.out.push('\n');
+            self.line = 0;
+        }
+
+        self.out.push_str(&run);
+        self.line += run.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(pattern: &Pattern) -> Vec<Vec<u32>> {
+        (0..pattern.height())
+            .map(|y| pattern.row(y).to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn parses_header() {
+        let pattern = parse("x = 3, y = 2, rule = B3/S23:T20,20\nobo$3o!", 10, 10).unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 2));
+        assert_eq!(pattern.metadata().rule.as_deref(), Some("B3/S23:T20,20"));
+        assert_eq!(rows(&pattern), [[1, 0, 1], [1, 1, 1]]);
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        assert!(parse("x = 3\nooo!", 10, 10).is_err());
+        assert!(parse("x = -1, y = 1\no!", 10, 10).is_err());
+        assert!(parse("#N Only comments\n", 10, 10).is_err());
+    }
+
+    #[test]
+    fn rejects_patterns_larger_than_limit_before_allocating() {
+        let huge = "x = 4000000000, y = 4000000000\no!";
+        assert!(parse(huge, u32::MAX, u32::MAX).is_err());
+        assert!(parse(huge, 100, 100).is_err());
+        assert!(parse("x = 11, y = 1\no!", 10, 10).is_err());
+    }
+
+    #[test]
+    fn rejects_cells_outside_header() {
+        assert!(parse("x = 2, y = 2\n3o!", 10, 10).is_err());
+        assert!(parse("x = 2, y = 2\n2$o!", 10, 10).is_err());
+        assert!(parse("x = 2, y = 2\n4294967295bo!", 10, 10).is_err());
+    }
+
+    #[test]
+    fn parses_multistate_cells() {
+        let pattern = parse("x = 5, y = 1, rule = Test\n.ABpAyO!", 10, 10).unwrap();
+        assert_eq!(rows(&pattern), [[0, 1, 2, 25, 255]]);
+        assert!(parse("x = 1, y = 1\nyP!", 10, 10).is_err());
+    }
+
+    #[test]
+    fn stops_at_terminator() {
+        let pattern = parse("x = 3, y = 1\n2o!\n#C trailing text ooo", 10, 10).unwrap();
+        assert_eq!(rows(&pattern), [[1, 1, 0]]);
+    }
+
+    #[test]
+    fn reads_comments() {
+        let contents = "#N Glider\n#O Richard K. Guy\n#C First\n#c Second\n#r 23/3\n\
+            x = 3, y = 3\nbob$2bo$3o!";
+        let metadata = parse(contents, 10, 10).unwrap().metadata().clone();
+        assert_eq!(metadata.name.as_deref(), Some("Glider"));
+        assert_eq!(metadata.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(metadata.comments, ["First", "Second"]);
+        assert_eq!(metadata.rule.as_deref(), Some("23/3"));
+    }
+
+    /// Pattern with cells set by function of coordinates
+    fn pattern(width: u32, height: u32, state: impl Fn(u32, u32) -> u32) -> Pattern {
+        let mut pattern = Pattern::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                pattern.set(x, y, state(x, y));
+            }
+        }
+
+        pattern
+    }
+
+    fn round_trip(pattern: &Pattern) -> Pattern {
+        parse(&write(pattern), pattern.width(), pattern.height()).unwrap()
+    }
+
+    #[test]
+    fn round_trips_two_state_pattern() {
+        let mut glider = pattern(3, 3, |x, y| [2, 5, 6, 7, 8].contains(&(x + y * 3)) as u32);
+        let metadata = glider.metadata_mut();
+        metadata.name = Some("Glider".into());
+        metadata.author = Some("Richard K. Guy".into());
+        metadata.comments = vec!["Smallest spaceship".into()];
+        metadata.rule = Some("B3/S23".into());
+
+        assert_eq!(round_trip(&glider), glider);
+        assert!(write(&glider).contains("bo$2bo$3o!"));
+    }
+
+    #[test]
+    fn round_trips_empty_rows_and_columns() {
+        let sparse = pattern(7, 6, |x, y| (x == 1 && y == 1 || x == 4 && y == 4) as u32);
+        assert_eq!(round_trip(&sparse), sparse);
+        assert_eq!(round_trip(&Pattern::new(4, 3)), Pattern::new(4, 3));
+    }
+
+    #[test]
+    fn round_trips_multistate_cells() {
+        let states = pattern(16, 16, |x, y| x + y * 16);
+        let written = write(&states);
+        assert!(written.contains(".ABC"));
+        assert!(written.contains("pA"));
+        assert!(written.contains("yO"));
+
+        assert_eq!(round_trip(&states), states);
+    }
+
+    #[test]
+    fn round_trips_canonical_rle_byte_for_byte() {
+        let wrapped = format!(
+            "x = 160, y = 1\n{}\n{}\n{}2o!\n",
+            "2o2b".repeat(17) + "2o",
+            "2b2o".repeat(17) + "2b",
+            "2o2b".repeat(4)
+        );
+
+        for canonical in [
+            "#N Glider\n#O Richard K. Guy\n#C Smallest spaceship\n\
+             x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n",
+            "x = 3, y = 4\no2$3o$bo!\n",
+            "x = 5, y = 1, rule = Test\n.ABpAyO!\n",
+            &wrapped,
+        ] {
+            let pattern = parse(canonical, 200, 10).unwrap();
+            assert_eq!(write(&pattern), canonical);
+        }
+
+        assert!(wrapped
+            .lines()
+            .skip(1)
+            .all(|line| line.len() <= LINE_LENGTH));
+    }
+
+    #[test]
+    fn merges_runs_when_writing() {
+        let pattern = parse("x = 5, y = 4\nooboo$$\n$b2o1o!", 10, 10).unwrap();
+        assert_eq!(write(&pattern), "x = 5, y = 4\n2ob2o3$b3o!\n");
+    }
+
+    #[test]
+    fn wraps_lines_at_70_columns() {
+        let noise = pattern(200, 40, |x, y| (x * 7 + y * 13) % 5 % 2);
+        let written = write(&noise);
+        let body: Vec<&str> = written.lines().skip(1).collect();
+
+        assert!(body.len() > 1);
+        assert!(body.iter().all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(round_trip(&noise), noise);
+
+        let states = pattern(300, 3, |x, y| (x + y) % MAX_STATES);
+        assert!(write(&states).lines().all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(round_trip(&states), states);
+    }
+}
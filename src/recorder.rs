@@ -0,0 +1,301 @@
+//! Recording of field's region to animated GIF or APNG.
+//!
+//! Frames are rendered offscreen with the same renderer as the window, one frame per generation.
+//! GIF frames are written to the file as soon as they are read back. APNG declares number of
+//! frames before them, so its frames are kept in temporary file until recording is finished.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use glam::Vec2;
+use wgpu::{Device, Queue};
+
+use crate::{
+    bindable::{Camera, Palette},
+    life::Life,
+    offscreen::{self, Offscreen},
+    renderer::{RenderMode, Renderer},
+};
+
+/// Duration of frame of animation in hundredths of second
+const FRAME_DELAY: u16 = 5;
+
+/// Format of recorded animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Gif,
+    Apng,
+}
+
+impl RecordFormat {
+    /// Format by name: `gif` or `apng`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gif" => Some(Self::Gif),
+            "apng" => Some(Self::Apng),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordFormat::Gif => "gif",
+            RecordFormat::Apng => "png",
+        }
+    }
+}
+
+/// Encoder of animation writing frames to file
+enum Encoder {
+    Gif(gif::Encoder<BufWriter<File>>),
+    /// File of animation and raw RGBA frames written to it when recording is finished
+    Apng {
+        file: File,
+        frames: BufWriter<File>,
+    },
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    /// Temporary file of APNG frames
+    frames_path: Option<PathBuf>,
+    /// Duration each generation is shown for in hundredths of second
+    delay: u16,
+    width: u32,
+    height: u32,
+
+    offscreen: Offscreen,
+    renderer: Renderer,
+
+    /// Taken when recording is finished
+    encoder: Option<Encoder>,
+    /// Number of recorded generations
+    frame_count: usize,
+    /// Generation of the last frame
+    generation: Option<u64>,
+}
+
+impl Recorder {
+    /// Creates recorder of `width`x`height` cells with bottom left corner at `x`, `y`
+    /// writing animation to `path`.
+    ///
+    /// Each cell is a square of `cell_size` pixels, each generation is shown for
+    /// `frames_per_generation` frames of 50 ms.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        path: &Path,
+        (x, y, width, height): (u32, u32, u32, u32),
+        cell_size: u32,
+        format: RecordFormat,
+        frames_per_generation: u32,
+        life: &Life,
+        palette: &Palette,
+        device: &Device,
+        queue: &Queue,
+    ) -> anyhow::Result<Self> {
+        let (pixels_w, pixels_h) = (width * cell_size, height * cell_size);
+
+        let delay =
+            FRAME_DELAY.saturating_mul(frames_per_generation.clamp(1, u16::MAX as u32) as u16);
+
+        let file = File::create(path)
+            .with_context(|| format!("Cannot write recording {}", path.display()))?;
+        let mut frames_path = None;
+        let encoder = match format {
+            RecordFormat::Gif => {
+                let file = BufWriter::new(file);
+                let mut encoder = gif::Encoder::new(file, pixels_w as u16, pixels_h as u16, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                Encoder::Gif(encoder)
+            }
+            RecordFormat::Apng => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let temp = std::env::temp_dir().join(format!("{}.frames", name));
+                let frames = File::create(&temp)
+                    .with_context(|| format!("Cannot write frames to {}", temp.display()))?;
+                frames_path = Some(temp);
+
+                Encoder::Apng {
+                    file,
+                    frames: BufWriter::new(frames),
+                }
+            }
+        };
+
+        let offscreen = Offscreen::new(pixels_w, pixels_h, device);
+
+        // Cells are centred at their coordinates
+        let mut camera = Camera::new(pixels_w, pixels_h, device);
+        camera.set_position(Vec2::new(
+            x as f32 + width as f32 / 2.0 - 0.5,
+            y as f32 + height as f32 / 2.0 - 0.5,
+        ));
+        camera.set_scale_factor(cell_size as f32);
+
+        let mut renderer = Renderer::new(life, palette, offscreen::FORMAT, device);
+        renderer.set_camera(&camera, queue);
+
+        Ok(Self {
+            path: path.to_owned(),
+            frames_path,
+            delay,
+            width: pixels_w,
+            height: pixels_h,
+
+            offscreen,
+            renderer,
+
+            encoder: Some(encoder),
+            frame_count: 0,
+            generation: None,
+        })
+    }
+
+    /// Number of recorded generations
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Renders current generation and writes it to file, if it isn't recorded yet
+    pub fn capture(
+        &mut self,
+        life: &Life,
+        palette: &Palette,
+        device: &Device,
+        queue: &Queue,
+    ) -> anyhow::Result<()> {
+        if self.generation == Some(life.generation()) {
+            return Ok(());
+        }
+        self.generation = Some(life.generation());
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Recording encoder"),
+        });
+
+        self.renderer.render(
+            RenderMode::Fullscreen,
+            life,
+            palette,
+            None,
+            self.offscreen.view(),
+            &mut encoder,
+            queue,
+        );
+        let mut pixels = self.offscreen.read(encoder, device, queue);
+
+        match &mut self.encoder {
+            Some(Encoder::Gif(encoder)) => {
+                let mut frame = gif::Frame::from_rgba_speed(
+                    self.width as u16,
+                    self.height as u16,
+                    &mut pixels,
+                    10,
+                );
+                frame.delay = self.delay;
+                encoder.write_frame(&frame)?;
+            }
+            Some(Encoder::Apng { frames, .. }) => frames.write_all(&pixels)?,
+            None => {}
+        }
+        self.frame_count += 1;
+
+        Ok(())
+    }
+
+    /// Finishes file of animation, returns its path
+    pub fn finish(mut self) -> anyhow::Result<PathBuf> {
+        match self.encoder.take() {
+            Some(Encoder::Gif(encoder)) => {
+                encoder.into_inner()?.flush()?;
+            }
+            Some(Encoder::Apng { file, mut frames }) => {
+                frames.flush()?;
+                let frames_path = self
+                    .frames_path
+                    .as_ref()
+                    .context("APNG has no frames file")?;
+                let frames = BufReader::new(File::open(frames_path)?);
+                let size = (self.width, self.height);
+                write_apng(
+                    BufWriter::new(file),
+                    frames,
+                    size,
+                    self.frame_count,
+                    self.delay,
+                )
+                .with_context(|| format!("Cannot write recording {}", self.path.display()))?;
+            }
+            None => {}
+        }
+
+        Ok(self.path.clone())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Some(path) = &self.frames_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Writes APNG of `count` raw RGBA frames, each shown for `delay` hundredths of second
+fn write_apng(
+    out: impl Write,
+    mut frames: impl Read,
+    (width, height): (u32, u32),
+    count: usize,
+    delay: u16,
+) -> anyhow::Result<()> {
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(count as u32, 0)?;
+    encoder.set_frame_delay(delay, 100)?;
+    let mut writer = encoder.write_header()?;
+
+    let mut pixels = vec![0; (width * height * 4) as usize];
+    for _ in 0..count {
+        frames.read_exact(&mut pixels)?;
+        writer.write_image_data(&pixels)?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_apng_with_number_of_frames() {
+        let frames: Vec<u8> = (0..3u8).flat_map(|i| [i; 2 * 2 * 4]).collect();
+        let mut apng = Vec::new();
+        write_apng(&mut apng, &frames[..], (2, 2), 3, 10).unwrap();
+
+        let mut reader = png::Decoder::new(&apng[..]).read_info().unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (3, 0));
+
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        for i in 0..3u8 {
+            reader.next_frame(&mut pixels).unwrap();
+            assert_eq!(pixels, [i; 2 * 2 * 4]);
+            let frame = reader.info().frame_control().unwrap();
+            assert_eq!((frame.delay_num, frame.delay_den), (10, 100));
+        }
+    }
+
+    #[test]
+    fn fails_on_missing_frames() {
+        let frames = [0u8; 2 * 2 * 4];
+        assert!(write_apng(Vec::new(), &frames[..], (2, 2), 2, 10).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Drawing of field to any texture view: window, screenshots and recordings.
+
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, TextureFormat, TextureView};
+
+use crate::{
+    bindable::{BinableToRenderPass, Camera, FieldView, HaveBindGroup, Palette},
+    density::DensityPyramid,
+    instanced::InstancedRenderer,
+    life::Life,
+    minimap::Minimap,
+    shader::Shader,
+};
+
+/// How cells are drawn.
+///
+/// Fullscreen pass costs the same for any number of alive cells, while quads cost only
+/// as much as there are alive or tinted cells in view, which is cheaper for sparse fields
+/// on large screens. When cells are smaller than a pixel both draw the density pyramid
+/// with the fullscreen pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Fullscreen pass looking up cell under each pixel
+    Fullscreen,
+    /// Quads of cells in view, culled on GPU
+    Instanced,
+}
+
+pub struct Renderer {
+    field_pipeline: RenderPipeline,
+    instanced: InstancedRenderer,
+
+    density: DensityPyramid,
+    field_view: FieldView,
+}
+
+impl Renderer {
+    pub fn new(life: &Life, palette: &Palette, format: TextureFormat, device: &Device) -> Self {
+        let density = DensityPyramid::new(life, device);
+        let field_view = FieldView::new(life.width(), life.height(), &density, device);
+
+        let shader = Shader::field(device, format);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Field pipeline layout"),
+            bind_group_layouts: &[
+                field_view.get_bind_layout(),
+                life.life_buffer().get_bind_layout(),
+                palette.get_bind_layout(),
+                life.rule_data().get_bind_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+        let field_pipeline = shader.create_pipeline(&layout, device);
+
+        let instanced = InstancedRenderer::new(life, palette, &field_view, device, format);
+
+        Self {
+            field_pipeline,
+            instanced,
+
+            density,
+            field_view,
+        }
+    }
+
+    pub fn density(&self) -> &DensityPyramid {
+        &self.density
+    }
+
+    pub fn field_view(&self) -> &FieldView {
+        &self.field_view
+    }
+
+    /// Follows camera, should be called when camera is updated
+    pub fn set_camera(&mut self, camera: &Camera, queue: &Queue) {
+        self.field_view.set_camera(camera, queue);
+    }
+
+    /// Shows grid with major lines every `major` cells, or hides it
+    pub fn set_grid(&mut self, grid: Option<u32>, queue: &Queue) {
+        self.field_view.set_grid(grid, queue);
+    }
+
+    /// Clears target with background of theme and draws field, then minimap over it if any
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        mode: RenderMode,
+        life: &Life,
+        palette: &Palette,
+        minimap: Option<&Minimap>,
+        target: &TextureView,
+        encoder: &mut CommandEncoder,
+        queue: &Queue,
+    ) {
+        // Many cells are under each pixel, quads of them would be drawn over each other
+        let zoomed_out = |view: &FieldView| view.cell_size() < 1.0;
+        let mode = match zoomed_out(&self.field_view) {
+            true => RenderMode::Fullscreen,
+            false => mode,
+        };
+
+        let field_zoomed_out = mode == RenderMode::Fullscreen && zoomed_out(&self.field_view);
+        if field_zoomed_out || minimap.is_some_and(|m| zoomed_out(m.field_view())) {
+            self.density.build(life, encoder);
+        }
+
+        if mode == RenderMode::Instanced {
+            self.instanced.cull(life, &self.field_view, encoder, queue);
+        }
+
+        let [r, g, b, a] = palette.theme().background.map(f64::from);
+        let life_buffer = life.life_buffer();
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Field pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        self.field_view
+            .bind_to_render_pass(&mut render_pass, 0, &[]);
+        life_buffer.bind_to_render_pass(&mut render_pass, 1, &[]);
+        palette.bind_to_render_pass(&mut render_pass, 2, &[]);
+        life.rule_data()
+            .bind_to_render_pass(&mut render_pass, 3, &[]);
+
+        match mode {
+            RenderMode::Fullscreen => {
+                render_pass.set_pipeline(&self.field_pipeline);
+                render_pass.draw(0..3, 0..1);
+            }
+            RenderMode::Instanced => self.instanced.draw(&mut render_pass),
+        }
+
+        // Minimap is always drawn by fullscreen pass
+        if let Some(minimap) = minimap {
+            let (x, y, width, height) = minimap.rect();
+            render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+            render_pass.set_pipeline(&self.field_pipeline);
+            minimap
+                .field_view()
+                .bind_to_render_pass(&mut render_pass, 0, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
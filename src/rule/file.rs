@@ -0,0 +1,55 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Context;
+
+/// Time between checks of modification time of rule file
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Rule source file on disk, tracks modifications for hot reloading
+pub struct RuleFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Time of last check of modification time
+    checked: Option<Instant>,
+}
+
+impl RuleFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            checked: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if file was modified since last load, file is checked at most every
+    /// `CHECK_INTERVAL`
+    pub fn changed(&mut self) -> bool {
+        if self.checked.is_some_and(|t| t.elapsed() < CHECK_INTERVAL) {
+            return false;
+        }
+        self.checked = Some(Instant::now());
+
+        let modified = std::fs::metadata(&self.path).and_then(|m| m.modified());
+        match modified {
+            Ok(modified) => self.modified != Some(modified),
+            Err(_) => false,
+        }
+    }
+
+    pub fn load(&mut self) -> anyhow::Result<String> {
+        self.modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok();
+
+        std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Cannot read rule file {}", self.path.display()))
+    }
+}
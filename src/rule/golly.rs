@@ -0,0 +1,799 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, bail, Context};
+
+use super::MAX_STATES;
+
+/// Cells of Moore neighbourhood in order they are visited by tree: `nw ne sw se n w e s c`
+const TREE_CELLS: usize = 9;
+
+/// Multi-state rule loaded from Golly's `.rule` file.
+///
+/// `@TABLE` and `@TREE` sections are both compiled into decision tree,
+/// flattened for compute kernel as `[states, root, nodes...]`. Each node is
+/// `states` entries: offsets of next nodes, or new states at the last level.
+pub struct RuleTree {
+    name: String,
+    source: String,
+    states: u32,
+    data: Vec<u32>,
+    colors: Vec<(u32, [u8; 3])>,
+}
+
+impl RuleTree {
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut name = None;
+        let mut section = String::new();
+        let mut table = Vec::new();
+        let mut tree = Vec::new();
+        let mut colors = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if let Some(header) = line.strip_prefix('@') {
+                let mut words = header.split_whitespace();
+                section = words.next().unwrap_or_default().to_owned();
+                if section == "RULE" {
+                    name = words.next().map(str::to_owned);
+                }
+                continue;
+            }
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let line = (number + 1, line);
+            match section.as_str() {
+                "TABLE" => table.push(line),
+                "TREE" => tree.push(line),
+                "COLORS" => colors.push(line),
+                _ => {}
+            }
+        }
+
+        let name = name.ok_or_else(|| anyhow!("Missing `@RULE` section"))?;
+        let builder = match (table.is_empty(), tree.is_empty()) {
+            (false, _) => parse_table(&table).context("Invalid `@TABLE` section")?,
+            (true, false) => parse_tree(&tree).context("Invalid `@TREE` section")?,
+            (true, true) => bail!("Rule `{}` has neither `@TABLE` nor `@TREE`", name),
+        };
+
+        let colors = parse_colors(&colors, builder.states).context("Invalid `@COLORS` section")?;
+
+        Ok(Self {
+            name,
+            source: contents.to_owned(),
+            states: builder.states,
+            data: builder.data,
+            colors,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Contents of rule file
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn states(&self) -> u32 {
+        self.states
+    }
+
+    pub fn data(&self) -> &[u32] {
+        &self.data
+    }
+
+    /// Colors of states from `@COLORS` section
+    pub fn colors(&self) -> &[(u32, [u8; 3])] {
+        &self.colors
+    }
+}
+
+/// Flattened tree with deduplicated nodes
+struct TreeBuilder {
+    states: u32,
+    data: Vec<u32>,
+    nodes: HashMap<Vec<u32>, u32>,
+}
+
+impl TreeBuilder {
+    fn new(states: u32) -> anyhow::Result<Self> {
+        if !(2..=MAX_STATES).contains(&states) {
+            bail!(
+                "Number of states must be in 2..={}, got {}",
+                MAX_STATES,
+                states
+            );
+        }
+
+        Ok(Self {
+            states,
+            data: vec![states, 0],
+            nodes: HashMap::new(),
+        })
+    }
+
+    fn add_node(&mut self, children: Vec<u32>) -> u32 {
+        if let Some(&offset) = self.nodes.get(&children) {
+            return offset;
+        }
+
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(&children);
+        self.nodes.insert(children, offset);
+
+        offset
+    }
+
+    fn set_root(&mut self, root: u32) {
+        self.data[1] = root;
+    }
+}
+
+/// Parses `key=value` or `key:value` header line
+fn parse_header<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let value = line.strip_prefix(key)?.trim_start();
+    let value = value.strip_prefix(['=', ':'])?;
+    Some(value.trim())
+}
+
+fn parse_number(value: &str, line: usize) -> anyhow::Result<u32> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("line {}: expected number, got `{}`", line, value))
+}
+
+fn parse_tree(lines: &[(usize, &str)]) -> anyhow::Result<TreeBuilder> {
+    let mut states = None;
+    let mut neighbours = None;
+    let mut nodes = Vec::new();
+
+    for &(number, line) in lines {
+        if let Some(v) = parse_header(line, "num_states") {
+            states = Some(parse_number(v, number)?);
+        } else if let Some(v) = parse_header(line, "num_neighbors") {
+            neighbours = Some(parse_number(v, number)?);
+        } else if parse_header(line, "num_nodes").is_none() {
+            let node = line
+                .split_whitespace()
+                .map(|v| parse_number(v, number))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            nodes.push((number, node));
+        }
+    }
+
+    let states = states.ok_or_else(|| anyhow!("Missing `num_states`"))?;
+    let neighbours = neighbours.ok_or_else(|| anyhow!("Missing `num_neighbors`"))?;
+    if neighbours != 4 && neighbours != 8 {
+        bail!("`num_neighbors` must be 4 or 8, got {}", neighbours);
+    }
+
+    let mut builder = TreeBuilder::new(states)?;
+    let mut offsets: Vec<(u32, u32)> = Vec::with_capacity(nodes.len());
+
+    for (number, node) in nodes {
+        if node.len() != states as usize + 1 {
+            bail!(
+                "line {}: node must have level and {} children",
+                number,
+                states
+            );
+        }
+
+        let level = node[0];
+        let children = node[1..]
+            .iter()
+            .map(|&child| match level {
+                1 if child < states => Ok(child),
+                1 => bail!("line {}: state {} is out of range", number, child),
+                _ => match offsets.get(child as usize) {
+                    Some(&(child_level, offset)) if child_level + 1 == level => Ok(offset),
+                    _ => bail!("line {}: invalid child node {}", number, child),
+                },
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let offset = builder.add_node(children);
+        offsets.push((level, offset));
+    }
+
+    let (level, mut root) = *offsets.last().ok_or_else(|| anyhow!("Tree has no nodes"))?;
+    if level != neighbours + 1 {
+        bail!(
+            "Root node must have level {}, got {}",
+            neighbours + 1,
+            level
+        );
+    }
+
+    // von Neumann tree visits `n w e s c`, corners are skipped
+    if neighbours == 4 {
+        for _ in 0..4 {
+            root = builder.add_node(vec![root; states as usize]);
+        }
+    }
+
+    builder.set_root(root);
+    Ok(builder)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Neighbourhood {
+    Moore,
+    VonNeumann,
+}
+
+impl Neighbourhood {
+    fn size(self) -> usize {
+        match self {
+            Neighbourhood::Moore => 8,
+            Neighbourhood::VonNeumann => 4,
+        }
+    }
+
+    /// Position in tree order of each table input: `C, N, NE, E, SE, S, SW, W, NW`
+    /// or `C, N, E, S, W`
+    fn tree_positions(self) -> &'static [usize] {
+        match self {
+            Neighbourhood::Moore => &[8, 4, 1, 6, 3, 7, 2, 5, 0],
+            Neighbourhood::VonNeumann => &[8, 4, 6, 7, 5],
+        }
+    }
+}
+
+/// Transition of table with explicit sets of states
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Transition {
+    /// Allowed states of center and neighbours in table order
+    inputs: Vec<Vec<u32>>,
+    output: u32,
+}
+
+fn parse_table(lines: &[(usize, &str)]) -> anyhow::Result<TreeBuilder> {
+    let mut states = None;
+    let mut neighbourhood = Neighbourhood::Moore;
+    let mut symmetries = String::from("none");
+    let mut variables: HashMap<String, Vec<u32>> = HashMap::new();
+    let mut transitions = Vec::new();
+    let mut seen = HashSet::new();
+
+    for &(number, line) in lines {
+        if let Some(v) = parse_header(line, "n_states") {
+            states = Some(parse_number(v, number)?);
+        } else if let Some(v) = parse_header(line, "neighborhood") {
+            neighbourhood = match v {
+                "Moore" => Neighbourhood::Moore,
+                "vonNeumann" => Neighbourhood::VonNeumann,
+                _ => bail!("line {}: unsupported neighborhood `{}`", number, v),
+            };
+        } else if let Some(v) = parse_header(line, "symmetries") {
+            symmetries = v.to_owned();
+        } else if let Some(v) = line.strip_prefix("var ") {
+            let (name, values) = parse_variable(v, &variables, number)?;
+            variables.insert(name, values);
+        } else {
+            let states = states.ok_or_else(|| anyhow!("line {}: `n_states` expected", number))?;
+            let tokens = tokenize(line, states);
+            if tokens.len() != neighbourhood.size() + 2 {
+                bail!(
+                    "line {}: transition must have {} states",
+                    number,
+                    neighbourhood.size() + 2
+                );
+            }
+
+            for transition in bind_variables(&tokens, &variables, states, number)? {
+                for transition in apply_symmetries(transition, neighbourhood, &symmetries, number)?
+                {
+                    if seen.insert(transition.clone()) {
+                        transitions.push(transition);
+                    }
+                }
+            }
+        }
+    }
+
+    let states = states.ok_or_else(|| anyhow!("Missing `n_states`"))?;
+    let mut builder = TreeBuilder::new(states)?;
+
+    // Convert to tree order, missing cells of von Neumann match anything
+    let any: Vec<u32> = (0..states).collect();
+    let transitions: Vec<(Vec<Vec<u32>>, u32)> = transitions
+        .into_iter()
+        .map(|t| {
+            let mut inputs = vec![any.clone(); TREE_CELLS];
+            for (input, &position) in t.inputs.into_iter().zip(neighbourhood.tree_positions()) {
+                inputs[position] = input;
+            }
+            (inputs, t.output)
+        })
+        .collect();
+
+    let candidates: Vec<usize> = (0..transitions.len()).collect();
+    let mut memo = HashMap::new();
+    let root = build_level(&mut builder, &transitions, 0, candidates, &mut memo);
+    builder.set_root(root);
+
+    Ok(builder)
+}
+
+/// Builds node of tree, `candidates` are transitions matching cells visited so far.
+/// First matching transition wins, cell keeps its state if none matches.
+fn build_level(
+    builder: &mut TreeBuilder,
+    transitions: &[(Vec<Vec<u32>>, u32)],
+    level: usize,
+    candidates: Vec<usize>,
+    memo: &mut HashMap<(usize, Vec<usize>), u32>,
+) -> u32 {
+    if let Some(&node) = memo.get(&(level, candidates.clone())) {
+        return node;
+    }
+
+    let children = (0..builder.states)
+        .map(|state| {
+            let matching: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&t| transitions[t].0[level].contains(&state))
+                .collect();
+
+            if level + 1 == TREE_CELLS {
+                matching.first().map_or(state, |&t| transitions[t].1)
+            } else {
+                build_level(builder, transitions, level + 1, matching, memo)
+            }
+        })
+        .collect();
+
+    let node = builder.add_node(children);
+    memo.insert((level, candidates), node);
+
+    node
+}
+
+/// Parses `name={a,b,c}`, values can be states or other variables
+fn parse_variable(
+    declaration: &str,
+    variables: &HashMap<String, Vec<u32>>,
+    line: usize,
+) -> anyhow::Result<(String, Vec<u32>)> {
+    let (name, values) = declaration
+        .split_once('=')
+        .ok_or_else(|| anyhow!("line {}: expected `var name={{...}}`", line))?;
+
+    let values = values.trim();
+    let values = values
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+        .ok_or_else(|| anyhow!("line {}: variable values must be in braces", line))?;
+
+    let mut result = Vec::new();
+    for value in values.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        match variables.get(value) {
+            Some(values) => result.extend_from_slice(values),
+            None => result.push(parse_number(value, line)?),
+        }
+    }
+
+    result.sort_unstable();
+    result.dedup();
+    Ok((name.trim().to_owned(), result))
+}
+
+/// Splits transition into states and variables.
+/// Single digit states may be written without separators, e.g. `0120000001`.
+fn tokenize(line: &str, states: u32) -> Vec<String> {
+    if line.contains([',', ' ', '\t']) || states > 10 {
+        line.split([',', ' ', '\t'])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_owned)
+            .collect()
+    } else {
+        line.chars().map(String::from).collect()
+    }
+}
+
+/// Expands variables used more than once into separate transitions, they must have same value
+fn bind_variables(
+    tokens: &[String],
+    variables: &HashMap<String, Vec<u32>>,
+    states: u32,
+    line: usize,
+) -> anyhow::Result<Vec<Transition>> {
+    let (inputs, output) = tokens.split_at(tokens.len() - 1);
+    let output = &output[0];
+
+    for token in tokens {
+        if !variables.contains_key(token) {
+            let state = parse_number(token, line)
+                .map_err(|_| anyhow!("line {}: unknown variable `{}`", line, token))?;
+            if state >= states {
+                bail!("line {}: state {} is out of range", line, state);
+            }
+        }
+    }
+
+    if variables.contains_key(output) && !inputs.contains(output) {
+        bail!("line {}: output variable `{}` is not bound", line, output);
+    }
+
+    let mut bound: Vec<&String> = inputs
+        .iter()
+        .filter(|t| variables.contains_key(*t))
+        .filter(|t| inputs.iter().filter(|o| o == t).count() > 1 || *t == output)
+        .collect();
+    bound.sort();
+    bound.dedup();
+
+    let mut assignments: Vec<HashMap<&String, u32>> = vec![HashMap::new()];
+    for name in bound {
+        assignments = assignments
+            .into_iter()
+            .flat_map(|a| {
+                variables[name].iter().map(move |&value| {
+                    let mut a = a.clone();
+                    a.insert(name, value);
+                    a
+                })
+            })
+            .collect();
+    }
+
+    let resolve = |token: &String, assignment: &HashMap<&String, u32>| -> Vec<u32> {
+        match (assignment.get(token), variables.get(token)) {
+            (Some(&value), _) => vec![value],
+            (None, Some(values)) => values.clone(),
+            (None, None) => vec![token.parse().unwrap()],
+        }
+    };
+
+    Ok(assignments
+        .iter()
+        .map(|a| Transition {
+            inputs: inputs.iter().map(|t| resolve(t, a)).collect(),
+            output: resolve(output, a)[0],
+        })
+        .collect())
+}
+
+/// Generates all transitions equivalent to `transition` under symmetries
+fn apply_symmetries(
+    transition: Transition,
+    neighbourhood: Neighbourhood,
+    symmetries: &str,
+    line: usize,
+) -> anyhow::Result<Vec<Transition>> {
+    let n = neighbourhood.size();
+    let rotation = |k: usize| (0..n).map(|i| (i + k) % n).collect::<Vec<_>>();
+    let reflection = |p: Vec<usize>| p.iter().map(|&i| (n - i) % n).collect::<Vec<_>>();
+
+    let rotations = |step: usize| (0..n).step_by(step).map(rotation).collect::<Vec<_>>();
+    let with_reflections = |p: Vec<Vec<usize>>| {
+        let reflected: Vec<_> = p.iter().cloned().map(reflection).collect();
+        p.into_iter().chain(reflected).collect::<Vec<_>>()
+    };
+
+    // Quarter turn moves neighbours by `n / 4` positions
+    let quarter = n / 4;
+    let permutations = match (symmetries, neighbourhood) {
+        ("none", _) => vec![rotation(0)],
+        ("rotate4", _) => rotations(quarter),
+        ("rotate8", Neighbourhood::Moore) => rotations(1),
+        ("reflect_horizontal", _) => with_reflections(vec![rotation(0)]),
+        ("rotate4reflect", _) => with_reflections(rotations(quarter)),
+        ("rotate8reflect", Neighbourhood::Moore) => with_reflections(rotations(1)),
+        ("permute", _) => return Ok(permute(transition)),
+        _ => bail!(
+            "line {}: unsupported symmetries `{}` for this neighborhood",
+            line,
+            symmetries
+        ),
+    };
+
+    Ok(permutations
+        .into_iter()
+        .map(|p| {
+            let mut inputs = vec![transition.inputs[0].clone()];
+            inputs.extend(p.iter().map(|&i| transition.inputs[i + 1].clone()));
+            Transition {
+                inputs,
+                output: transition.output,
+            }
+        })
+        .collect())
+}
+
+/// All distinct orders of neighbours
+fn permute(transition: Transition) -> Vec<Transition> {
+    let mut neighbours = transition.inputs[1..].to_vec();
+    neighbours.sort();
+
+    let mut result = Vec::new();
+    loop {
+        let mut inputs = vec![transition.inputs[0].clone()];
+        inputs.extend_from_slice(&neighbours);
+        result.push(Transition {
+            inputs,
+            output: transition.output,
+        });
+
+        if !next_permutation(&mut neighbours) {
+            return result;
+        }
+    }
+}
+
+/// Rearranges into next permutation in lexicographic order, returns false after the last one
+fn next_permutation<T: Ord>(v: &mut [T]) -> bool {
+    let i = match (1..v.len()).rev().find(|&i| v[i - 1] < v[i]) {
+        Some(i) => i,
+        None => return false,
+    };
+
+    let j = (i..v.len()).rev().find(|&j| v[i - 1] < v[j]).unwrap();
+    v.swap(i - 1, j);
+    v[i..].reverse();
+
+    true
+}
+
+/// Parses `state r g b` lines and `r1 g1 b1 r2 g2 b2` gradient of live states
+fn parse_colors(lines: &[(usize, &str)], states: u32) -> anyhow::Result<Vec<(u32, [u8; 3])>> {
+    let mut colors = Vec::new();
+
+    for &(number, line) in lines {
+        let values = line
+            .split([' ', '\t', ','])
+            .filter(|v| !v.is_empty())
+            .map(|v| parse_number(v, number))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let rgb = |v: &[u32]| match v {
+            &[r, g, b] if r <= 255 && g <= 255 && b <= 255 => Ok([r as u8, g as u8, b as u8]),
+            _ => Err(anyhow!(
+                "line {}: color components must be in 0..=255",
+                number
+            )),
+        };
+
+        match values.len() {
+            4 if values[0] < states => colors.push((values[0], rgb(&values[1..])?)),
+            4 => bail!("line {}: state {} is out of range", number, values[0]),
+            6 => {
+                let from = rgb(&values[0..3])?.map(f32::from);
+                let to = rgb(&values[3..6])?.map(f32::from);
+                for state in 1..states {
+                    let t = if states > 2 {
+                        (state - 1) as f32 / (states - 2) as f32
+                    } else {
+                        0.0
+                    };
+                    let color = [0, 1, 2].map(|i| (from[i] + (to[i] - from[i]) * t).round() as u8);
+                    colors.push((state, color));
+                }
+            }
+            _ => bail!(
+                "line {}: expected `state r g b` or `r1 g1 b1 r2 g2 b2`",
+                number
+            ),
+        }
+    }
+
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Table order of neighbours: `N, NE, E, SE, S, SW, W, NW`
+    const NEIGHBOURS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+    /// New state of cell, `cells` are in tree order: `nw ne sw se n w e s c`
+    fn evaluate(tree: &RuleTree, cells: [u32; TREE_CELLS]) -> u32 {
+        let data = tree.data();
+        let mut node = data[1];
+        for (level, &cell) in cells.iter().enumerate() {
+            node = data[(node + cell) as usize];
+            if level + 1 == TREE_CELLS {
+                break;
+            }
+        }
+
+        node
+    }
+
+    /// New state of cell with neighbours in table order
+    fn evaluate_table(tree: &RuleTree, center: u32, neighbours: [u32; 8]) -> u32 {
+        let positions = Neighbourhood::Moore.tree_positions();
+        let mut cells = [0; TREE_CELLS];
+        cells[positions[0]] = center;
+        for (&state, &position) in neighbours.iter().zip(&positions[1..]) {
+            cells[position] = state;
+        }
+
+        evaluate(tree, cells)
+    }
+
+    /// Neighbours in table order with alive cells at names of `NEIGHBOURS`
+    fn alive(names: &[&str]) -> [u32; 8] {
+        NEIGHBOURS.map(|n| names.contains(&n) as u32)
+    }
+
+    fn table(states: u32, symmetries: &str, body: &str) -> RuleTree {
+        let contents = format!(
+            "@RULE Test\n@TABLE\nn_states:{}\nneighborhood:Moore\nsymmetries:{}\n{}",
+            states, symmetries, body
+        );
+        RuleTree::parse(&contents).unwrap()
+    }
+
+    #[test]
+    fn conway_table_matches_b3_s23() {
+        let life = table(
+            2,
+            "permute",
+            "var a={0,1}\nvar b={0,1}\nvar c={0,1}\nvar d={0,1}\n\
+             var e={0,1}\nvar f={0,1}\nvar g={0,1}\nvar h={0,1}\n\
+             0,1,1,1,0,0,0,0,0,1\n\
+             1,1,1,0,0,0,0,0,0,1\n\
+             1,1,1,1,0,0,0,0,0,1\n\
+             1,a,b,c,d,e,f,g,h,0\n",
+        );
+        assert_eq!((life.name(), life.states()), ("Test", 2));
+
+        for cells in 0..1u32 << TREE_CELLS {
+            let cells: [u32; TREE_CELLS] = std::array::from_fn(|i| cells >> i & 1);
+            let count: u32 = cells[..8].iter().sum();
+            let expected = (count == 3 || cells[8] == 1 && count == 2) as u32;
+            assert_eq!(evaluate(&life, cells), expected, "{:?}", cells);
+        }
+    }
+
+    #[test]
+    fn maps_table_order_to_tree_order() {
+        // Tree visits `nw ne sw se n w e s c`
+        let tree_index = [4, 1, 6, 3, 7, 2, 5, 0];
+
+        for (k, name) in NEIGHBOURS.iter().enumerate() {
+            let mut line = ["0"; 10];
+            line[k + 1] = "1";
+            line[9] = "1";
+            let rule = table(2, "none", &line.join(","));
+
+            for (i, &index) in tree_index.iter().enumerate() {
+                let mut cells = [0; TREE_CELLS];
+                cells[index] = 1;
+                assert_eq!(evaluate(&rule, cells), (i == k) as u32, "{}", name);
+            }
+        }
+
+        // Center is visited last
+        let rule = table(2, "none", "1,0,0,0,0,0,0,0,0,0");
+        let mut cells = [0; TREE_CELLS];
+        cells[8] = 1;
+        assert_eq!(evaluate(&rule, cells), 0);
+    }
+
+    #[test]
+    fn binds_variables_used_more_than_once() {
+        let rule = table(3, "none", "var a={1,2}\nvar b={a,0}\n0,a,a,b,0,0,0,0,0,a\n");
+
+        assert_eq!(evaluate_table(&rule, 0, [1, 1, 0, 0, 0, 0, 0, 0]), 1);
+        assert_eq!(evaluate_table(&rule, 0, [2, 2, 1, 0, 0, 0, 0, 0]), 2);
+        // Different values of bound variable don't match
+        assert_eq!(evaluate_table(&rule, 0, [1, 2, 0, 0, 0, 0, 0, 0]), 0);
+        // Variable used once matches any of its values
+        assert_eq!(evaluate_table(&rule, 0, [2, 2, 2, 0, 0, 0, 0, 0]), 2);
+
+        let unbound = "@RULE Test\n@TABLE\nn_states:3\nvar a={1,2}\nvar b={1,2}\n\
+            0,a,0,0,0,0,0,0,0,b\n";
+        assert!(RuleTree::parse(unbound).is_err());
+        let unknown = "@RULE Test\n@TABLE\nn_states:3\n0,x,0,0,0,0,0,0,0,1\n";
+        assert!(RuleTree::parse(unknown).is_err());
+    }
+
+    /// Neighbours which alone make dead cell alive
+    fn births(rule: &RuleTree) -> Vec<&'static str> {
+        NEIGHBOURS
+            .into_iter()
+            .filter(|n| evaluate_table(rule, 0, alive(&[n])) == 1)
+            .collect()
+    }
+
+    #[test]
+    fn applies_symmetries() {
+        let north = "0,1,0,0,0,0,0,0,0,1";
+        let north_east = "0,0,1,0,0,0,0,0,0,1";
+
+        assert_eq!(births(&table(2, "none", north)), ["N"]);
+        assert_eq!(births(&table(2, "rotate4", north)), ["N", "E", "S", "W"]);
+        assert_eq!(
+            births(&table(2, "rotate4", north_east)),
+            ["NE", "SE", "SW", "NW"]
+        );
+        assert_eq!(births(&table(2, "rotate8", north)), NEIGHBOURS);
+        assert_eq!(
+            births(&table(2, "reflect_horizontal", north_east)),
+            ["NE", "NW"]
+        );
+        assert_eq!(births(&table(2, "permute", north)), NEIGHBOURS);
+
+        // Rotations keep neighbours adjacent, permutations don't
+        let pair = "0,1,1,0,0,0,0,0,0,1";
+        let rotate8 = table(2, "rotate8", pair);
+        let permute = table(2, "permute", pair);
+        assert_eq!(evaluate_table(&rotate8, 0, alive(&["E", "SE"])), 1);
+        assert_eq!(evaluate_table(&rotate8, 0, alive(&["N", "S"])), 0);
+        assert_eq!(evaluate_table(&permute, 0, alive(&["N", "S"])), 1);
+
+        let contents = "@RULE Test\n@TABLE\nn_states:2\nsymmetries:rotate3\n0,0,0,0,0,0,0,0,0,1\n";
+        assert!(RuleTree::parse(contents).is_err());
+    }
+
+    /// Tree of `neighbours` setting cell to state of the first visited cell
+    fn first_cell_tree(neighbours: u32) -> String {
+        let mut lines = vec![format!("num_states=2\nnum_neighbors={}", neighbours)];
+        // Nodes of level 1 are 0 and 1, each next level adds two nodes
+        lines.push("1 0 0\n1 1 1".into());
+        for level in 2..=neighbours {
+            let zero = (level - 2) * 2;
+            lines.push(format!("{} {} {}", level, zero, zero));
+            lines.push(format!("{} {} {}", level, zero + 1, zero + 1));
+        }
+        let zero = (neighbours - 1) * 2;
+        lines.push(format!("{} {} {}", neighbours + 1, zero, zero + 1));
+
+        format!("@RULE Test\n@TREE\n{}\n", lines.join("\n"))
+    }
+
+    #[test]
+    fn reads_trees_in_golly_order() {
+        // Moore tree visits `nw` first
+        let moore = RuleTree::parse(&first_cell_tree(8)).unwrap();
+        for (i, expected) in [1, 0, 0, 0, 0, 0, 0, 0, 0].into_iter().enumerate() {
+            let mut cells = [0; TREE_CELLS];
+            cells[i] = 1;
+            assert_eq!(evaluate(&moore, cells), expected);
+        }
+
+        // von Neumann tree visits `n w e s c`, so `n` first
+        let von_neumann = RuleTree::parse(&first_cell_tree(4)).unwrap();
+        for (i, expected) in [0, 0, 0, 0, 1, 0, 0, 0, 0].into_iter().enumerate() {
+            let mut cells = [0; TREE_CELLS];
+            cells[i] = 1;
+            assert_eq!(evaluate(&von_neumann, cells), expected);
+        }
+
+        let wrong_root = first_cell_tree(8).replace("num_neighbors=8", "num_neighbors=4");
+        assert!(RuleTree::parse(&wrong_root).is_err());
+    }
+
+    #[test]
+    fn parses_colors() {
+        let rule = "@RULE Test\n@TABLE\nn_states:4\n0,1,0,0,0,0,0,0,0,1\n\
+            @COLORS\n0 0 0 255 255 255\n3 255 0 0 # red\n";
+        let colors = RuleTree::parse(rule).unwrap().colors().to_vec();
+        assert_eq!(
+            colors,
+            [
+                (1, [0, 0, 0]),
+                (2, [128, 128, 128]),
+                (3, [255, 255, 255]),
+                (3, [255, 0, 0])
+            ]
+        );
+
+        let out_of_range = rule.replace("3 255 0 0", "4 255 0 0");
+        assert!(RuleTree::parse(&out_of_range).is_err());
+        let too_bright = rule.replace("3 255 0 0", "3 256 0 0");
+        assert!(RuleTree::parse(&too_bright).is_err());
+    }
+}
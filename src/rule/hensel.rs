@@ -0,0 +1,271 @@
+use anyhow::{anyhow, bail};
+
+/// Size of lookup table, one entry per 3x3 neighbourhood
+const TABLE_SIZE: usize = 512;
+
+/// Representative neighbourhood of each Hensel letter for 0 to 4 neighbours.
+///
+/// Neighbours are encoded as `NW N NE W E SW S SE` from the highest bit to the lowest.
+/// Letters of 5 to 8 neighbours are complements of letters of `8 - n` neighbours.
+const LETTERS: [&[(char, u8)]; 5] = [
+    &[('c', 0x00)],
+    &[('c', 0x01), ('e', 0x02)],
+    &[
+        ('c', 0x05),
+        ('e', 0x0a),
+        ('k', 0x0c),
+        ('a', 0x03),
+        ('i', 0x18),
+        ('n', 0x24),
+    ],
+    &[
+        ('c', 0x25),
+        ('e', 0x1a),
+        ('k', 0x32),
+        ('a', 0x0b),
+        ('i', 0x07),
+        ('n', 0x0d),
+        ('y', 0x31),
+        ('q', 0x26),
+        ('j', 0x0e),
+        ('r', 0x19),
+    ],
+    &[
+        ('c', 0xa5),
+        ('e', 0x5a),
+        ('k', 0x33),
+        ('a', 0x0f),
+        ('i', 0x1d),
+        ('n', 0x27),
+        ('y', 0x35),
+        ('q', 0x36),
+        ('j', 0x3a),
+        ('r', 0x1b),
+        ('t', 0x39),
+        ('w', 0x2e),
+        ('z', 0x3c),
+    ],
+];
+
+/// Offsets of neighbours in order of bits, from the highest. North is `+y`.
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Two-state rule as a lookup table indexed by 3x3 neighbourhood.
+///
+/// Index bits are `NW N NE W C E SW S SE` from the highest bit to the lowest.
+pub struct RuleTable {
+    name: String,
+    table: Vec<u32>,
+}
+
+impl RuleTable {
+    /// Parses isotropic non-totalistic rulestring in Hensel notation, e.g. `B2-a/S12`.
+    ///
+    /// Conditions may come in either order, e.g. `S23/B3`. Outer totalistic rules like
+    /// `B3/S23` and `S/B` form `23/3` are accepted too.
+    pub fn parse(rulestring: &str) -> anyhow::Result<Self> {
+        let rule = rulestring.trim();
+        let (birth, survival) = split_rulestring(rule)
+            .ok_or_else(|| anyhow!("Invalid rulestring `{}`, expected `B.../S...`", rule))?;
+
+        let birth_set = parse_conditions(&birth)
+            .map_err(|e| anyhow!("Invalid birth conditions in `{}`: {}", rule, e))?;
+        let survival_set = parse_conditions(&survival)
+            .map_err(|e| anyhow!("Invalid survival conditions in `{}`: {}", rule, e))?;
+
+        let table = (0..TABLE_SIZE)
+            .map(|idx| {
+                let center = idx & 0x10 != 0;
+                let neighbours = ((idx >> 1) & 0xf0) | (idx & 0x0f);
+                let set = if center { &survival_set } else { &birth_set };
+
+                set[neighbours] as u32
+            })
+            .collect();
+
+        Ok(Self {
+            name: format!("B{}/S{}", birth, survival),
+            table,
+        })
+    }
+
+    pub fn conway() -> Self {
+        Self::parse("B3/S23").unwrap()
+    }
+
+    /// Canonical rulestring
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn table(&self) -> &[u32] {
+        &self.table
+    }
+}
+
+/// Splits rulestring into birth and survival conditions
+fn split_rulestring(rule: &str) -> Option<(String, String)> {
+    let lower = rule.to_ascii_lowercase();
+    let mut birth = None;
+    let mut survival = None;
+
+    for part in lower.split(['/', '_']).filter(|p| !p.is_empty()) {
+        if let Some(b) = part.strip_prefix('b') {
+            birth = Some(b.to_owned());
+        } else if let Some(s) = part.strip_prefix('s') {
+            survival = Some(s.to_owned());
+        }
+    }
+
+    // Legacy S/B notation, e.g. `23/3`
+    if birth.is_none() && survival.is_none() {
+        let (s, b) = lower.split_once('/')?;
+        return Some((b.to_owned(), s.to_owned()));
+    }
+
+    // `B3S23` or `S23B3` without separator
+    match (&birth, &survival) {
+        (Some(b), None) => {
+            if let Some((b, s)) = b.split_once('s') {
+                return Some((b.to_owned(), s.to_owned()));
+            }
+        }
+        (None, Some(s)) => {
+            if let Some((s, b)) = s.split_once('b') {
+                return Some((b.to_owned(), s.to_owned()));
+            }
+        }
+        _ => {}
+    }
+
+    Some((birth.unwrap_or_default(), survival.unwrap_or_default()))
+}
+
+/// Parses conditions like `2-a3` into set of matching 8-bit neighbourhoods
+fn parse_conditions(conditions: &str) -> anyhow::Result<[bool; 256]> {
+    let mut set = [false; 256];
+    let mut chars = conditions.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let count = c
+            .to_digit(10)
+            .filter(|&d| d <= 8)
+            .ok_or_else(|| anyhow!("unexpected `{}`", c))? as usize;
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut letters = Vec::new();
+        while let Some(letter) = chars.next_if(char::is_ascii_lowercase) {
+            letters.push(letter);
+        }
+
+        if negated && letters.is_empty() {
+            bail!("`{}-` must be followed by letters", count);
+        }
+
+        let available = letters_of(count);
+        for letter in &letters {
+            if !available.iter().any(|(l, _)| l == letter) {
+                bail!("no `{}{}` neighbourhood", count, letter);
+            }
+        }
+
+        for (letter, _) in available {
+            if letters.is_empty() || letters.contains(&letter) != negated {
+                for neighbours in configurations(count, letter) {
+                    set[neighbours as usize] = true;
+                }
+            }
+        }
+    }
+
+    Ok(set)
+}
+
+/// Letters available for given neighbours count with their representative neighbourhood
+fn letters_of(count: usize) -> Vec<(char, u8)> {
+    if count <= 4 {
+        LETTERS[count].to_vec()
+    } else {
+        LETTERS[8 - count].iter().map(|&(l, n)| (l, !n)).collect()
+    }
+}
+
+/// All neighbourhoods of given letter, obtained by rotations and reflections
+fn configurations(count: usize, letter: char) -> Vec<u8> {
+    let (_, representative) = letters_of(count)
+        .into_iter()
+        .find(|&(l, _)| l == letter)
+        .unwrap();
+
+    let mut result: Vec<u8> = (0..8)
+        .map(|symmetry| transform(representative, symmetry))
+        .collect();
+    result.sort_unstable();
+    result.dedup();
+
+    result
+}
+
+/// Applies one of 8 symmetries of square to neighbourhood
+fn transform(neighbours: u8, symmetry: u32) -> u8 {
+    let mut result = 0;
+    for (bit, &(x, y)) in NEIGHBOURS.iter().enumerate() {
+        if neighbours & (0x80 >> bit) == 0 {
+            continue;
+        }
+
+        let (x, y) = match symmetry {
+            0 => (x, y),
+            1 => (-y, x),
+            2 => (-x, -y),
+            3 => (y, -x),
+            4 => (-x, y),
+            5 => (y, x),
+            6 => (x, -y),
+            _ => (-y, -x),
+        };
+
+        let target = NEIGHBOURS.iter().position(|&n| n == (x, y)).unwrap();
+        result |= 0x80 >> target;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_both_orders() {
+        let conway = RuleTable::conway();
+        for rule in ["S23/B3", "b3_s23", "B3S23", "S23B3", "23/3"] {
+            let table = RuleTable::parse(rule).unwrap();
+            assert_eq!(table.name(), "B3/S23", "{}", rule);
+            assert_eq!(table.table(), conway.table(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn keeps_hensel_letters_in_s_b_order() {
+        let a = RuleTable::parse("B2-a/S12").unwrap();
+        let b = RuleTable::parse("S12/B2-a").unwrap();
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.table(), b.table());
+    }
+
+    #[test]
+    fn rejects_unknown_letters() {
+        assert!(RuleTable::parse("B3x/S23").is_err());
+        assert!(RuleTable::parse("S23/B9").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+mod file;
+pub use file::RuleFile;
+
+mod hensel;
+pub use hensel::RuleTable;
+
+mod golly;
+pub use golly::RuleTree;
+
+/// Max number of cell states
+pub const MAX_STATES: u32 = 256;
+
+/// Format of rule's source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleFormat {
+    Rulestring,
+    Wgsl,
+    Golly,
+}
+
+/// Rule of simulation
+pub enum Rule {
+    /// User supplied WGSL `transition` function
+    Transition { name: String, source: String },
+    /// Lookup table indexed by 3x3 neighbourhood
+    Table(RuleTable),
+    /// Multi-state decision tree from Golly's rule file
+    Tree(RuleTree),
+}
+
+impl Rule {
+    /// Creates rule from contents of rule file, format is chosen by extension
+    pub fn from_file(path: &Path, contents: &str) -> anyhow::Result<Self> {
+        let name = path.display().to_string();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("wgsl") => Ok(Self::Transition {
+                name,
+                source: contents.to_owned(),
+            }),
+            Some("rule") => Ok(Self::Tree(RuleTree::parse(contents)?)),
+            _ => Ok(Self::Table(RuleTable::parse(contents)?)),
+        }
+    }
+
+    /// Creates rule from source returned by [`Rule::source`]
+    pub fn from_source(format: RuleFormat, name: &str, source: &str) -> anyhow::Result<Self> {
+        match format {
+            RuleFormat::Rulestring => Ok(Self::Table(RuleTable::parse(source)?)),
+            RuleFormat::Wgsl => Ok(Self::Transition {
+                name: name.to_owned(),
+                source: source.to_owned(),
+            }),
+            RuleFormat::Golly => Ok(Self::Tree(RuleTree::parse(source)?)),
+        }
+    }
+
+    /// Source rule can be created from again
+    pub fn source(&self) -> (RuleFormat, &str) {
+        match self {
+            Rule::Transition { source, .. } => (RuleFormat::Wgsl, source),
+            Rule::Table(table) => (RuleFormat::Rulestring, table.name()),
+            Rule::Tree(tree) => (RuleFormat::Golly, tree.source()),
+        }
+    }
+
+    /// Creates rule named in pattern file: rulestring or name of `.rule` file.
+    ///
+    /// Rule files are searched in `dir` and then in `rules` directory.
+    pub fn from_name(name: &str, dir: &Path) -> anyhow::Result<Self> {
+        // Bounded grids, e.g. `B3/S23:T100,100`, aren't supported
+        let name = name.split(':').next().unwrap_or_default().trim();
+        if let Ok(table) = RuleTable::parse(name) {
+            return Ok(Self::Table(table));
+        }
+
+        let file_name = format!("{}.rule", name);
+        let path = [dir, Path::new("rules")]
+            .iter()
+            .map(|d| d.join(&file_name))
+            .find(|p| p.is_file())
+            .ok_or_else(|| anyhow::anyhow!("Unknown rule `{}`", name))?;
+
+        let contents = RuleFile::new(&path).load()?;
+        Self::from_file(&path, &contents)
+    }
+
+    /// Rulestring or name of file rule was loaded from
+    pub fn name(&self) -> &str {
+        match self {
+            Rule::Transition { name, .. } => name,
+            Rule::Table(table) => table.name(),
+            Rule::Tree(tree) => tree.name(),
+        }
+    }
+
+    /// Name of rule for `rule =` of pattern files: rulestring or name of `.rule` file.
+    ///
+    /// Transition functions are files of this program and have none.
+    pub fn pattern_name(&self) -> Option<&str> {
+        match self {
+            Rule::Transition { .. } => None,
+            Rule::Table(table) => Some(table.name()),
+            Rule::Tree(tree) => Some(tree.name()),
+        }
+    }
+
+    /// Number of states, transition functions may use any
+    pub fn states(&self) -> u32 {
+        self.declared_states().unwrap_or(MAX_STATES)
+    }
+
+    /// Number of states if rule declares it, transition functions don't
+    pub fn declared_states(&self) -> Option<u32> {
+        match self {
+            Rule::Transition { .. } => None,
+            Rule::Table(_) => Some(2),
+            Rule::Tree(tree) => Some(tree.states()),
+        }
+    }
+
+    /// Colors of states defined by rule
+    pub fn colors(&self) -> &[(u32, [u8; 3])] {
+        match self {
+            Rule::Tree(tree) => tree.colors(),
+            _ => &[],
+        }
+    }
+
+    /// Data for compute kernel, if rule needs any
+    pub fn data(&self) -> Option<&[u32]> {
+        match self {
+            Rule::Transition { .. } => None,
+            Rule::Table(table) => Some(table.table()),
+            Rule::Tree(tree) => Some(tree.data()),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Images of field: rendered offscreen on GPU or rasterised on CPU, saved as PNG.
+
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::Context;
+use wgpu::{Device, Queue};
+
+use crate::{
+    bindable::{Camera, Color, Palette},
+    life::Life,
+    offscreen::{self, Offscreen},
+    pattern::Pattern,
+    renderer::{RenderMode, Renderer},
+};
+
+/// RGBA image, rows of pixels go from the top
+pub struct Screenshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Renders field seen by camera to `width`x`height` pixels, camera must be of the same size
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        camera: &Camera,
+        (width, height): (u32, u32),
+        mode: RenderMode,
+        grid: Option<u32>,
+        life: &Life,
+        palette: &Palette,
+        device: &Device,
+        queue: &Queue,
+    ) -> Self {
+        let offscreen = Offscreen::new(width, height, device);
+
+        let mut renderer = Renderer::new(life, palette, offscreen::FORMAT, device);
+        renderer.set_camera(camera, queue);
+        renderer.set_grid(grid, queue);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Screenshot encoder"),
+        });
+        renderer.render(
+            mode,
+            life,
+            palette,
+            None,
+            offscreen.view(),
+            &mut encoder,
+            queue,
+        );
+
+        Self {
+            width,
+            height,
+            pixels: offscreen.read(encoder, device, queue),
+        }
+    }
+
+    /// Draws cells of pattern on CPU, each cell is a square of `cell_size` pixels, with grid
+    /// lines blended over them as by the GPU renderers.
+    ///
+    /// Tints of region rules aren't drawn. Colors of states are encoded to sRGB as by the
+    /// offscreen texture.
+    pub fn rasterize(
+        pattern: &Pattern,
+        cell_size: u32,
+        colors: &[Color],
+        grid: Option<&Grid>,
+    ) -> Self {
+        let (width, height) = (pattern.width() * cell_size, pattern.height() * cell_size);
+
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (i, j) = (x / cell_size, y / cell_size);
+                let state = pattern.get(i, j) as usize;
+                let mut color = colors[state.min(colors.len() - 1)];
+
+                if let Some(grid) = grid {
+                    // Position inside of cell, field's y goes up
+                    let inside_x = ((x % cell_size) as f32 + 0.5) / cell_size as f32;
+                    let inside_y = 1.0 - ((y % cell_size) as f32 + 0.5) / cell_size as f32;
+                    let cell = (grid.origin.0 + i, grid.origin.1 + pattern.height() - 1 - j);
+                    color = grid.blend(color, cell, (inside_x, inside_y), cell_size as f32);
+                }
+
+                pixels.extend_from_slice(&to_srgb(color));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Cannot write screenshot {}", path.display()))?;
+
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+/// Grid drawn over cells rasterised on CPU
+pub struct Grid {
+    /// Cells between major lines, 0 for no major lines
+    pub major: u32,
+    /// Field coordinates of bottom left cell of pattern
+    pub origin: (u32, u32),
+    /// Colors of minor and major lines
+    pub colors: [Color; 2],
+}
+
+impl Grid {
+    /// Blends lines over color of `cell` at `inside` position of it, same as `grid` of
+    /// `shaders/draw.wgsl`
+    fn blend(&self, color: Color, cell: (u32, u32), inside: (f32, f32), cell_size: f32) -> Color {
+        let distance = |t: f32| t.min(1.0 - t) * cell_size;
+        let (dx, dy) = (distance(inside.0), distance(inside.1));
+        let coverage = (1.0 - dx.min(dy)).clamp(0.0, 1.0);
+
+        // Index of nearest edge, edge `n` is between cells `n - 1` and `n`
+        let edge = |c: u32, t: f32| c + (t >= 0.5) as u32;
+        let mut major = 0.0;
+        if self.major > 0 {
+            let on_x = dx <= dy && edge(cell.0, inside.0) % self.major == 0;
+            let on_y = dy <= dx && edge(cell.1, inside.1) % self.major == 0;
+            if on_x || on_y {
+                major = smoothstep(16.0, 32.0, cell_size * self.major as f32);
+            }
+        }
+
+        let minor = smoothstep(4.0, 8.0, cell_size);
+        let [minor_color, major_color] = self.colors;
+        let line = [0, 1, 2, 3].map(|i| minor_color[i] + (major_color[i] - minor_color[i]) * major);
+        let t = coverage * minor.max(major) * line[3];
+
+        [0, 1, 2, 3].map(|i| {
+            let target = if i == 3 { 1.0 } else { line[i] };
+            color[i] + (target - color[i]) * t
+        })
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn to_srgb(color: Color) -> [u8; 4] {
+    let encode = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    };
+
+    let [r, g, b, a] = color;
+    [encode(r), encode(g), encode(b), a.clamp(0.0, 1.0)].map(|c| (c * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = [0.0, 0.0, 0.0, 1.0];
+    const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+
+    /// Pattern of one row with cells of states
+    fn row(states: &[u32]) -> Pattern {
+        let mut pattern = Pattern::new(states.len() as u32, 1);
+        for (x, &state) in states.iter().enumerate() {
+            pattern.set(x as u32, 0, state);
+        }
+
+        pattern
+    }
+
+    #[test]
+    fn rasterizes_cells_with_palette() {
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        let screenshot = Screenshot::rasterize(&row(&[0, 1, 2, 9]), 2, &[BLACK, WHITE, gray], None);
+        assert_eq!(screenshot.size(), (8, 2));
+
+        // Sixth pixel of each row is of state 2, states out of palette use its last color
+        let pixel = |x: usize, y: usize| &screenshot.pixels[(y * 8 + x) * 4..][..4];
+        for y in 0..2 {
+            assert_eq!(pixel(0, y), [0, 0, 0, 255]);
+            assert_eq!(pixel(3, y), [255, 255, 255, 255]);
+            assert_eq!(pixel(5, y), [188, 188, 188, 255]);
+            assert_eq!(pixel(7, y), [188, 188, 188, 255]);
+        }
+    }
+
+    #[test]
+    fn blends_grid_lines_at_cell_edges() {
+        let grid = Grid {
+            major: 0,
+            origin: (0, 0),
+            colors: [WHITE, WHITE],
+        };
+        let screenshot = Screenshot::rasterize(&row(&[0]), 16, &[BLACK], Some(&grid));
+
+        // Line is 1 pixel wide, split between pixels on both sides of edge
+        let pixel = |x: usize, y: usize| screenshot.pixels[(y * 16 + x) * 4];
+        assert_eq!(pixel(8, 8), 0);
+        assert!(pixel(0, 8) > 0 && pixel(15, 8) > 0);
+        assert!(pixel(8, 0) > 0 && pixel(8, 15) > 0);
+    }
+
+    #[test]
+    fn saves_png() {
+        let screenshot = Screenshot::rasterize(&row(&[1, 0, 1]), 3, &[BLACK, WHITE], None);
+        let path = std::env::temp_dir().join(format!("screenshot-test-{}.png", std::process::id()));
+        screenshot.save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(bytes[..8], *b"\x89PNG\r\n\x1a\n");
+        // IHDR chunk: width, height, bit depth and RGBA color type
+        assert_eq!(bytes[12..16], *b"IHDR");
+        assert_eq!(bytes[16..20], 9u32.to_be_bytes());
+        assert_eq!(bytes[20..24], 3u32.to_be_bytes());
+        assert_eq!(bytes[24..26], [8, 6]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Saved state of simulation: gzip compressed JSON with version of its schema.
+//!
+//! Sessions of older versions are upgraded by migrations when loaded.
+
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{bindable::Boundary, rule::RuleFormat};
+
+/// Version of session schema written by this build
+const VERSION: u64 = 2;
+
+/// Migration from version `i + 1` to `i + 2` is at index `i`
+const MIGRATIONS: &[fn(&mut Value) -> anyhow::Result<()>] = &[add_rule_regions];
+
+/// Version 2 saves region rules and rule map, older sessions have none
+fn add_rule_regions(value: &mut Value) -> anyhow::Result<()> {
+    value["region_rules"] = Value::Array(Vec::new());
+    value["rule_map"] = Value::Null;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRule {
+    pub format: RuleFormat,
+    pub name: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCamera {
+    pub position: [f32; 2],
+    /// Size of cell in pixels
+    #[serde(deserialize_with = "positive")]
+    pub scale: f32,
+}
+
+/// Rejects scales the view can't be built with
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let scale = f32::deserialize(deserializer)?;
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(serde::de::Error::custom(format!(
+            "camera scale must be positive, got {}",
+            scale
+        )));
+    }
+
+    Ok(scale)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// The whole field as RLE pattern
+    pub field: String,
+    pub rule: SessionRule,
+    /// Rulestrings of region rules, rule `i` is in slot `i + 1`
+    pub region_rules: Vec<String>,
+    /// Rule slots of all cells as RLE pattern, `None` if all cells use the main rule
+    pub rule_map: Option<String>,
+    pub generation: u64,
+    pub boundary: Boundary,
+    pub camera: SessionCamera,
+    pub paused: bool,
+    /// Seed of initial random soup
+    pub seed: u64,
+}
+
+impl Session {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let mut value = serde_json::to_value(self)?;
+        value["version"] = VERSION.into();
+
+        let file = File::create(path)
+            .with_context(|| format!("Cannot write session file {}", path.display()))?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        serde_json::to_writer(&mut encoder, &value)?;
+        encoder.finish()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Whether file looks like session: it is gzip compressed
+    pub fn sniff(path: &Path) -> bool {
+        let mut magic = [0; 2];
+        File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok_and(|_| magic == [0x1f, 0x8b])
+    }
+
+    /// Loads session, upgrading it from older versions
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Cannot read session file {}", path.display()))?;
+        let mut contents = String::new();
+        GzDecoder::new(file)
+            .read_to_string(&mut contents)
+            .with_context(|| format!("Invalid session file {}", path.display()))?;
+
+        let mut value: Value = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid session file {}", path.display()))?;
+
+        let version = value["version"]
+            .as_u64()
+            .context("Session file has no version")?;
+        if version == 0 || version > VERSION {
+            bail!(
+                "Session version {} is not supported, latest is {}",
+                version,
+                VERSION
+            );
+        }
+
+        for migration in &MIGRATIONS[(version - 1) as usize..] {
+            migration(&mut value)?;
+        }
+
+        serde_json::from_value(value)
+            .with_context(|| format!("Invalid session file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        Session {
+            field: "x = 3, y = 1\n3o!\n".into(),
+            rule: SessionRule {
+                format: RuleFormat::Rulestring,
+                name: "B3/S23".into(),
+                source: "B3/S23".into(),
+            },
+            region_rules: vec!["B36/S23".into()],
+            rule_map: Some("x = 3, y = 1\nbo!\n".into()),
+            generation: 42,
+            boundary: Boundary::Dead,
+            camera: SessionCamera {
+                position: [1.5, -2.0],
+                scale: 0.25,
+            },
+            paused: true,
+            seed: 7,
+        }
+    }
+
+    /// Path of temporary file unique for test
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("session-{}-{}.life", name, std::process::id()))
+    }
+
+    /// Writes gzip compressed JSON to file and loads it as session
+    fn load_value(value: &Value, name: &str) -> anyhow::Result<Session> {
+        let path = temp_path(name);
+        let mut encoder = GzEncoder::new(File::create(&path)?, Compression::default());
+        serde_json::to_writer(&mut encoder, value)?;
+        encoder.finish()?;
+
+        let session = Session::load(&path);
+        std::fs::remove_file(&path)?;
+        session
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let path = temp_path("round-trip");
+        session().save(&path).unwrap();
+        assert!(Session::sniff(&path));
+
+        let loaded = Session::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), session());
+    }
+
+    #[test]
+    fn migrates_version_1() {
+        let mut value = serde_json::to_value(session()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("region_rules");
+        object.remove("rule_map");
+        object.insert("version".into(), 1.into());
+
+        let loaded = load_value(&value, "v1").unwrap();
+        assert!(loaded.region_rules.is_empty());
+        assert_eq!(loaded.rule_map, None);
+        assert_eq!(loaded.field, session().field);
+        assert_eq!(loaded.camera, session().camera);
+    }
+
+    #[test]
+    fn rejects_unsupported_versions_and_invalid_scale() {
+        let mut value = serde_json::to_value(session()).unwrap();
+        for version in [0, VERSION + 1] {
+            value["version"] = version.into();
+            assert!(load_value(&value, "version").is_err());
+        }
+
+        value["version"] = VERSION.into();
+        for scale in [0.0, -1.0] {
+            value["camera"]["scale"] = scale.into();
+            assert!(load_value(&value, "scale").is_err());
+        }
+        value["camera"]["scale"] = Value::Null;
+        assert!(load_value(&value, "scale").is_err());
+    }
+}
@@ -1,9 +1,40 @@
 use wgpu::{
-    include_wgsl, ColorTargetState, Device, FragmentState, ShaderModule, TextureFormat,
+    include_wgsl, ColorTargetState, Device, FragmentState, PipelineLayout, PrimitiveState,
+    RenderPipeline, ShaderModule, ShaderModuleDescriptor, ShaderSource, TextureFormat,
     VertexBufferLayout, VertexState,
 };
 
-use crate::{bindable::CellPos, model::Vertex};
+use crate::{
+    bindable::{CellInstances, CellPos},
+    model::Vertex,
+};
+
+/// Field view, field and rule map bindings of shaders reading field
+const VIEW: &str = include_str!("../shaders/view.wgsl");
+
+const VIEW_MARKER: &str = "// @view";
+
+/// Palette, grid and region tints of shaders drawing cells, needs [`VIEW`]
+const DRAW: &str = include_str!("../shaders/draw.wgsl");
+
+const DRAW_MARKER: &str = "// @draw";
+
+/// Shader reading field with shared declarations spliced at `// @view` and `// @draw` markers.
+///
+/// Fullscreen pass and instanced quads draw cells the same way, so they share everything
+/// but the way they cover the view.
+pub fn field_module(name: &'static str, template: &str, device: &Device) -> ShaderModule {
+    device.create_shader_module(ShaderModuleDescriptor {
+        label: Some(name),
+        source: ShaderSource::Wgsl(splice(template).into()),
+    })
+}
+
+fn splice(template: &str) -> String {
+    template
+        .replacen(VIEW_MARKER, VIEW, 1)
+        .replacen(DRAW_MARKER, DRAW, 1)
+}
 
 pub struct Shader {
     module: ShaderModule,
@@ -12,10 +43,43 @@ pub struct Shader {
 }
 
 impl Shader {
-    pub fn new(device: &Device, texture_format: TextureFormat) -> Self {
-        let module = device.create_shader_module(include_wgsl!("../shaders/shader.wgsl"));
+    /// Fullscreen shader of cells, draws 3 vertices without buffers
+    pub fn field(device: &Device, texture_format: TextureFormat) -> Self {
+        let template = include_str!("../shaders/field.wgsl");
+        let module = field_module("field.wgsl", template, device);
+        Self::new(module, Vec::new(), texture_format)
+    }
 
+    /// Shader of cells instanced by indices of culled cells
+    pub fn cells(device: &Device, texture_format: TextureFormat) -> Self {
+        let template = include_str!("../shaders/cells.wgsl");
+        let module = field_module("cells.wgsl", template, device);
+        let vertex_buffer_layout = vec![Vertex::desc(), CellInstances::desc()];
+        Self::new(module, vertex_buffer_layout, texture_format)
+    }
+
+    /// Shader of translucent preview of pattern, uses only camera
+    pub fn ghost(device: &Device, texture_format: TextureFormat) -> Self {
+        let module = include_wgsl!("../shaders/ghost.wgsl");
         let vertex_buffer_layout = vec![Vertex::desc(), CellPos::desc()];
+        Self::from_module(module, vertex_buffer_layout, device, texture_format)
+    }
+
+    fn from_module(
+        descriptor: ShaderModuleDescriptor,
+        vertex_buffer_layout: Vec<VertexBufferLayout<'static>>,
+        device: &Device,
+        texture_format: TextureFormat,
+    ) -> Self {
+        let module = device.create_shader_module(descriptor);
+        Self::new(module, vertex_buffer_layout, texture_format)
+    }
+
+    fn new(
+        module: ShaderModule,
+        vertex_buffer_layout: Vec<VertexBufferLayout<'static>>,
+        texture_format: TextureFormat,
+    ) -> Self {
         let color_target_states = vec![Some(ColorTargetState {
             format: texture_format,
             blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -29,7 +93,7 @@ impl Shader {
         }
     }
 
-    pub fn vertex_state(&self) -> VertexState {
+    pub fn vertex_state(&self) -> VertexState<'_> {
         VertexState {
             module: &self.module,
             entry_point: "vs_main",
@@ -37,11 +101,58 @@ impl Shader {
         }
     }
 
-    pub fn frag_state(&self) -> FragmentState {
+    pub fn frag_state(&self) -> FragmentState<'_> {
         FragmentState {
             module: &self.module,
             entry_point: "fs_main",
             targets: &self.color_target_states,
         }
     }
+
+    /// Creates pipeline drawing with this shader
+    pub fn create_pipeline(&self, layout: &PipelineLayout, device: &Device) -> RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render pipeline"),
+            layout: Some(layout),
+            vertex: self.vertex_state(),
+            fragment: Some(self.frag_state()),
+            primitive: PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    use super::*;
+
+    #[test]
+    fn spliced_field_shaders_are_valid() {
+        let templates = [
+            ("field.wgsl", include_str!("../shaders/field.wgsl")),
+            ("cells.wgsl", include_str!("../shaders/cells.wgsl")),
+            ("cull.wgsl", include_str!("../shaders/cull.wgsl")),
+        ];
+
+        for (name, template) in templates {
+            let source = splice(template);
+            let module = naga::front::wgsl::parse_str(&source)
+                .unwrap_or_else(|e| panic!("{}", e.emit_to_string_with_path(&source, name)));
+            Validator::new(ValidationFlags::all(), Capabilities::empty())
+                .validate(&module)
+                .unwrap_or_else(|e| panic!("{}: {:?}", name, e));
+        }
+    }
 }
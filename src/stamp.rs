@@ -0,0 +1,95 @@
+use glam::Vec2;
+use wgpu::Device;
+
+use crate::{
+    bindable::{CellPos, CellPosInstances},
+    pattern::Pattern,
+};
+
+/// Pattern of library being placed with the cursor
+pub struct Stamp {
+    /// Index in library
+    index: usize,
+    /// Rotated and flipped pattern
+    pattern: Pattern,
+
+    /// Cell of bottom left corner of pattern, `None` until placed at cursor
+    origin: Option<(i64, i64)>,
+    /// Alive cells of pattern at origin, drawn as translucent preview
+    ghost: Option<CellPosInstances>,
+}
+
+impl Stamp {
+    pub fn new(index: usize, pattern: Pattern) -> Self {
+        Self {
+            index,
+            pattern,
+            origin: None,
+            ghost: None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    pub fn rotate(&mut self, clockwise: bool) {
+        self.pattern = match clockwise {
+            true => self.pattern.rotated(),
+            false => self.pattern.rotated().rotated().rotated(),
+        };
+        self.origin = None;
+    }
+
+    pub fn flip(&mut self) {
+        self.pattern = self.pattern.flipped();
+        self.origin = None;
+    }
+
+    /// Cell of bottom left corner of pattern centred at `cell`
+    pub fn origin_at(&self, cell: Vec2) -> (i64, i64) {
+        (
+            cell.x.round() as i64 - self.pattern.width() as i64 / 2,
+            cell.y.round() as i64 - self.pattern.height() as i64 / 2,
+        )
+    }
+
+    /// Moves preview to be centred at `cell`
+    pub fn move_to(&mut self, cell: Vec2, device: &Device) {
+        let origin = self.origin_at(cell);
+        if self.origin == Some(origin) {
+            return;
+        }
+        self.origin = Some(origin);
+
+        // Rows of pattern go from the top, north is +y
+        let height = self.pattern.height();
+        let mut positions = Vec::new();
+        for y in 0..height {
+            for (x, &state) in self.pattern.row(y).iter().enumerate() {
+                if state > 0 {
+                    positions.push(CellPos {
+                        pos: [
+                            (origin.0 + x as i64) as f32,
+                            (origin.1 + (height - 1 - y) as i64) as f32,
+                        ],
+                        idx: 0,
+                    });
+                }
+            }
+        }
+
+        self.ghost = match positions.is_empty() {
+            true => None,
+            false => Some(CellPosInstances::new(positions, device)),
+        };
+    }
+
+    pub fn ghost(&self) -> Option<&CellPosInstances> {
+        self.ghost.as_ref()
+    }
+}
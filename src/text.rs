@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use wgpu::{util::StagingBelt, CommandEncoder, Device, TextureFormat, TextureView};
 
 use ab_glyph::FontArc;
@@ -11,10 +13,7 @@ pub struct FpsText {
 impl FpsText {
     pub fn new(device: &Device, render_format: TextureFormat) -> Self {
         let staging_belt = StagingBelt::new(1024);
-        let font =
-            FontArc::try_from_slice(include_bytes!("../fonts/LiberationMono-Bold.ttf")).unwrap();
-
-        let brush = GlyphBrushBuilder::using_font(font).build(device, render_format);
+        let brush = GlyphBrushBuilder::using_font(font()).build(device, render_format);
 
         Self {
             staging_belt,
@@ -52,3 +51,89 @@ impl FpsText {
         self.staging_belt.recall();
     }
 }
+
+/// How long message is shown
+const MESSAGE_DURATION: Duration = Duration::from_secs(8);
+
+/// Message shown at the bottom of screen for a while, e.g. error
+pub struct MessageText {
+    staging_belt: StagingBelt,
+    brush: GlyphBrush<()>,
+
+    /// Text, its color and when it's shown
+    message: Option<(String, [f32; 4], Instant)>,
+}
+
+impl MessageText {
+    pub fn new(device: &Device, render_format: TextureFormat) -> Self {
+        let staging_belt = StagingBelt::new(1024);
+        let brush = GlyphBrushBuilder::using_font(font()).build(device, render_format);
+
+        Self {
+            staging_belt,
+            brush,
+            message: None,
+        }
+    }
+
+    /// Replaces shown message
+    pub fn info(&mut self, message: String) {
+        self.message = Some((message, [0.0, 1.0, 1.0, 1.0], Instant::now()));
+    }
+
+    /// Replaces shown message with error
+    pub fn error(&mut self, message: String) {
+        self.message = Some((message, [1.0, 0.3, 0.3, 1.0], Instant::now()));
+    }
+
+    pub fn draw(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        target: &TextureView,
+        (width, height): (u32, u32),
+    ) {
+        let (message, color) = match &self.message {
+            Some((message, color, shown)) if shown.elapsed() < MESSAGE_DURATION => {
+                (message, *color)
+            }
+            _ => {
+                self.message = None;
+                return;
+            }
+        };
+
+        let lines = message.lines().count() as f32;
+        let text_render = Text::new(message).with_color(color).with_scale(20.0);
+
+        let section = Section::default()
+            .add_text(text_render)
+            .with_screen_position((10.0, height as f32 - 10.0 - 20.0 * lines))
+            .with_bounds((width as f32 - 20.0, height as f32));
+
+        self.brush.queue(section);
+
+        self.brush
+            .draw_queued(
+                device,
+                &mut self.staging_belt,
+                encoder,
+                target,
+                width,
+                height,
+            )
+            .unwrap();
+    }
+
+    pub fn submit(&mut self) {
+        self.staging_belt.finish();
+    }
+
+    pub fn recall(&mut self) {
+        self.staging_belt.recall();
+    }
+}
+
+fn font() -> FontArc {
+    FontArc::try_from_slice(include_bytes!("../fonts/LiberationMono-Bold.ttf")).unwrap()
+}
@@ -0,0 +1,160 @@
+//! Colors of field: built-in themes and themes loaded from JSON files.
+//!
+//! Theme file sets colors as `#rrggbb` or `#rrggbbaa`, missing colors are taken from `dark`:
+//! ```json
+//! {
+//!     "name": "solarized",
+//!     "background": "#002b36",
+//!     "dead": "#073642",
+//!     "alive": "#b58900",
+//!     "last_state": "#dc322f",
+//!     "grid": "#586e75",
+//!     "grid_major": "#93a1a1",
+//!     "outline": "#2aa198"
+//! }
+//! ```
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{bail, Context};
+
+use crate::bindable::Color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    /// Beyond edges of field
+    pub background: Color,
+    pub dead: Color,
+    pub alive: Color,
+    /// Color of the last state of multi-state rules, states between are blended from `alive`
+    pub last_state: Color,
+    pub grid: Color,
+    /// Lines every few cells of grid
+    pub grid_major: Color,
+    /// Outline of part of pattern larger than the field loaded into it
+    pub outline: Color,
+}
+
+/// Color from 8-bit components
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".into(),
+            background: rgb(16, 16, 20),
+            dead: rgb(36, 36, 44),
+            alive: rgb(64, 220, 120),
+            last_state: rgb(40, 90, 230),
+            grid: rgb(56, 56, 68),
+            grid_major: rgb(90, 90, 110),
+            outline: rgb(77, 204, 255),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "light".into(),
+            background: rgb(200, 200, 200),
+            dead: rgb(250, 250, 250),
+            alive: rgb(24, 24, 24),
+            last_state: rgb(40, 100, 220),
+            grid: rgb(220, 220, 220),
+            grid_major: rgb(170, 170, 170),
+            outline: rgb(0, 120, 200),
+        }
+    }
+
+    /// White cells on black with red to yellow states, as in Golly
+    pub fn golly() -> Self {
+        Self {
+            name: "golly".into(),
+            background: rgb(48, 48, 48),
+            dead: rgb(0, 0, 0),
+            alive: rgb(255, 255, 255),
+            last_state: rgb(255, 255, 0),
+            grid: rgb(32, 32, 32),
+            grid_major: rgb(64, 64, 64),
+            outline: rgb(0, 200, 255),
+        }
+    }
+
+    /// Orange and sky blue of Okabe-Ito palette, distinguishable with color vision deficiency
+    pub fn colorblind() -> Self {
+        Self {
+            name: "colorblind".into(),
+            background: rgb(0, 0, 0),
+            dead: rgb(24, 24, 24),
+            alive: rgb(230, 159, 0),
+            last_state: rgb(86, 180, 233),
+            grid: rgb(48, 48, 48),
+            grid_major: rgb(96, 96, 96),
+            outline: rgb(0, 158, 115),
+        }
+    }
+
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::dark(),
+            Self::light(),
+            Self::golly(),
+            Self::colorblind(),
+        ]
+    }
+
+    /// Loads theme file, its name is file name if not set
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read theme file {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Self::parse(&contents, &name)
+            .with_context(|| format!("Invalid theme file {}", path.display()))
+    }
+
+    fn parse(contents: &str, name: &str) -> anyhow::Result<Self> {
+        let mut values: HashMap<String, String> = serde_json::from_str(contents)?;
+
+        let mut theme = Self::dark();
+        theme.name = values.remove("name").unwrap_or_else(|| name.to_owned());
+
+        for (key, value) in values {
+            let color = match key.as_str() {
+                "background" => &mut theme.background,
+                "dead" => &mut theme.dead,
+                "alive" => &mut theme.alive,
+                "last_state" => &mut theme.last_state,
+                "grid" => &mut theme.grid,
+                "grid_major" => &mut theme.grid_major,
+                "outline" => &mut theme.outline,
+                _ => bail!("Unknown color `{}`", key),
+            };
+            *color = parse_color(&value).with_context(|| format!("Invalid color of `{}`", key))?;
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa`
+fn parse_color(s: &str) -> anyhow::Result<Color> {
+    let hex = s.trim().trim_start_matches('#');
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        bail!("Expected `#rrggbb` or `#rrggbbaa`, got `{}`", s);
+    }
+
+    let mut color = [1.0; 4];
+    for (i, component) in color.iter_mut().enumerate().take(hex.len() / 2) {
+        let value = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .with_context(|| format!("Invalid hex color `{}`", s))?;
+        *component = value as f32 / 255.0;
+    }
+
+    Ok(color)
+}